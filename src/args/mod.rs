@@ -49,6 +49,7 @@ pub struct Argument {
     sub_arg: Option<usize>,
     id: ArgumentID,
     action: Option<Action>,
+    repeat: bool,
 }
 
 impl Argument {
@@ -60,6 +61,7 @@ impl Argument {
             sub_arg: None,
             id: 0,
             action: None,
+            repeat: false,
         }
     }
 
@@ -82,6 +84,12 @@ impl Argument {
         self
     }
 
+    /// Collect the values of every use instead of keeping only the last one.
+    pub fn repeatable(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
     pub(crate) fn set_id(&mut self, id: usize) {
         self.id = id
     }
@@ -178,7 +186,6 @@ impl Args {
         let longest = self
             .stored
             .values()
-            .into_iter()
             .map(|t| {
                 let mut temp = t.matches.join(" | ");
                 if t.numbers > 0 {
@@ -300,7 +307,7 @@ impl Args {
             let query = format!("{ctx}#{arg}");
             let query2 = format!("0#{arg}");
 
-            if self.stored.get(&query).is_some() {
+            if self.stored.contains_key(&query) {
                 if let Some(argument) = self.stored.get(&query) {
                     ctx = argument.id;
                     let mut i = index;
@@ -333,8 +340,7 @@ impl Args {
                         action.borrow_mut()(values.clone());
                     }
 
-                    let pa = ParsedArgument::new(values);
-                    hm.insert(query, pa.clone());
+                    let pa = store(&mut hm, query, argument, values);
                     idem.insert(argument.id, pa);
                 }
             } else if let Some(argument) = self.stored.get(&query2) {
@@ -353,7 +359,7 @@ impl Args {
                     let q1 = format!("{ctx}#{value}");
                     let q2 = format!("0#{value}");
 
-                    if self.stored.get(&q1).is_some() || self.stored.get(&q2).is_some() {
+                    if self.stored.contains_key(&q1) || self.stored.contains_key(&q2) {
                         break;
                     } else {
                         values.push(value.to_string());
@@ -375,8 +381,7 @@ impl Args {
                     action.borrow_mut()(values.clone());
                 }
 
-                let pa = ParsedArgument::new(values);
-                hm.insert(query2, pa.clone());
+                let pa = store(&mut hm, query2, argument, values);
                 idem.insert(argument.id, pa);
             } else if let Some(argument) = self.stored.values().find(|t| t.matches.contains(arg)) {
                 if let Some(parent) = argument.sub_arg {
@@ -392,3 +397,53 @@ impl Args {
         Ok(ParsedArgs { hm })
     }
 }
+
+/// Store the values of a parsed argument, repeatable arguments keep the values of earlier uses.
+fn store(
+    hm: &mut HashMap<String, ParsedArgument>,
+    key: String,
+    argument: &Argument,
+    values: Vec<String>,
+) -> ParsedArgument {
+    let parsed = hm
+        .entry(key)
+        .and_modify(|parsed| {
+            if argument.repeat {
+                parsed.values.extend(values.iter().cloned());
+            } else {
+                parsed.values = values.clone();
+            }
+        })
+        .or_insert_with(|| ParsedArgument::new(values));
+    parsed.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeatable() {
+        let mut args = Args::new();
+        args.add_arg(Argument::new().alias("--set").count(1).repeatable());
+        args.add("--config", 1, "");
+        let parsed = args
+            .parse(Some(vec![
+                "lightfetch",
+                "--set",
+                "ART.mode=kitty",
+                "--config",
+                "a.ini",
+                "--set",
+                "FETCH.reverse=true",
+                "--config",
+                "b.ini",
+            ]))
+            .unwrap();
+        assert_eq!(
+            parsed.get("--set").unwrap().val(),
+            &vec!["ART.mode=kitty", "FETCH.reverse=true"]
+        );
+        assert_eq!(parsed.get("--config").unwrap().val(), &vec!["b.ini"]);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::util::hyperstr::Ansi;
+use crate::util::unicode::Unicode;
 use core::fmt::Display;
 
 extern crate alloc;
@@ -132,10 +134,12 @@ impl<'a> Builder<'a> {
             for item in &self.inner {
                 let wall_item = item.get(i).unwrap_or(&"");
                 line += wall_item;
-                if wall_item.len() < self.tabsize
+                // Measure what the terminal will actually draw, escape sequences take up no cells.
+                let width = wall_item.strip_ansi_colors().width();
+                if width < self.tabsize
                     && self.inner.iter().position(|f| f == item) != Some(self.inner.len() - 1)
                 {
-                    line += &create_spaces(self.tabsize - wall_item.len());
+                    line += &create_spaces(self.tabsize - width);
                 }
             }
             i += 1;
@@ -222,8 +226,8 @@ pub(crate) mod builder_tests {
     /// ## A macro for logging the tests in a more stylish way.
     /// Use it together with the func! macro to get a cool result.
     /// # Example
-    /// ```
-    /// #[test]
+    /// ```ignore
+    /// // #[test]
     /// fn test_function() {
     /// log!("test_function", "Output");
     /// }
@@ -0,0 +1,173 @@
+use crate::config::cache::variables::Variables;
+use crate::config::cache::{now, Cache};
+use crate::error::{ErrorLevel, LightError};
+use crate::util::hyperstr::{GRAY, GREEN, LIGHT_RED, RESET, YELLOW};
+
+/// All subcommands of `--cache`.
+pub const COMMANDS: [&str; 4] = ["list", "clear", "prune", "stats"];
+
+/// Run a `--cache` subcommand.
+///
+/// # Arguments:
+/// * `command`: One of `COMMANDS`.
+/// * `cache`: The image cache.
+/// * `variables`: The variable cache.
+///
+/// # Returns:
+/// A `LightError` if the subcommand doesn't exist.
+///
+pub(crate) fn run(
+    command: &str,
+    cache: &mut Cache,
+    variables: &Variables,
+) -> Result<(), LightError> {
+    let now = now();
+    match command {
+        "list" => {
+            let entries = cache.entries();
+            if entries.is_empty() {
+                println!("{GRAY}The cache is empty.{RESET}");
+            }
+            for entry in entries.iter().rev() {
+                let source = match entry.meta.source.as_str() {
+                    "" => "unknown source",
+                    source => source,
+                };
+                println!(
+                    "{YELLOW}{}{RESET} {} {GRAY}| {} | created {} ago | used {} ago{RESET}",
+                    entry.hash,
+                    source,
+                    bytes(entry.size),
+                    age(now, entry.meta.created),
+                    age(now, entry.meta.accessed)
+                );
+                if !entry.meta.params.is_empty() {
+                    println!("    {GRAY}{}{RESET}", entry.meta.params);
+                }
+            }
+        }
+        "clear" => {
+            let images = cache.clear();
+            let variables = if variables.clear() {
+                "and the"
+            } else {
+                "but no"
+            };
+            println!("{GREEN}Deleted {images} image(s) {variables} cached variables.{RESET}");
+        }
+        "prune" => {
+            let pruned = cache.prune();
+            // Expired variables would be fetched again anyway.
+            let expired = variables.load().is_none() && variables.clear();
+            for hash in &pruned {
+                println!("{GRAY}Deleted {YELLOW}{hash}{RESET}");
+            }
+            println!(
+                "{GREEN}Pruned {} image(s){}.{RESET}",
+                pruned.len(),
+                if expired {
+                    " and the expired variables"
+                } else {
+                    ""
+                }
+            );
+        }
+        "stats" => {
+            let entries = cache.entries();
+            let total: u64 = entries.iter().map(|e| e.size).sum();
+            println!("{GRAY}Path:{RESET} {}", cache.dir.display());
+            println!("{GRAY}Images:{RESET} {} ({})", entries.len(), bytes(total));
+            if let Some(oldest) = entries
+                .iter()
+                .map(|e| e.meta.created)
+                .filter(|c| *c > 0)
+                .min()
+            {
+                println!("{GRAY}Oldest image:{RESET} {} old", age(now, oldest));
+            }
+            if let Some(last) = entries.last() {
+                println!(
+                    "{GRAY}Last used:{RESET} {} {GRAY}{} ago{RESET}",
+                    last.hash,
+                    age(now, last.meta.accessed)
+                );
+            }
+            match variables.created() {
+                Some(created) => {
+                    let state = match variables.load() {
+                        Some(_) => "valid",
+                        None => "expired",
+                    };
+                    println!(
+                        "{GRAY}Variables:{RESET} cached {} ago, {state}",
+                        age(now, created)
+                    );
+                }
+                None => println!("{GRAY}Variables:{RESET} not cached"),
+            }
+        }
+        _ => {
+            return Err(LightError::new(
+                format!(
+                    "Cache command {LIGHT_RED}'{}'{RESET} invalid {GRAY}| Available: {}{RESET}",
+                    command,
+                    COMMANDS.map(|c| format!("'{}'", c)).join(", ")
+                ),
+                ErrorLevel::Error,
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Format a size in bytes, e.g. `12.3 KiB`.
+fn bytes(size: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, units[0])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+/// Format the time since a timestamp in its largest unit, e.g. `3h`.
+/// Entries from older versions don't have a timestamp, they show up as `?`.
+fn age(now: u64, then: u64) -> String {
+    if then == 0 {
+        return "?".to_string();
+    }
+    let secs = now.saturating_sub(then);
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / 60 / 60),
+        s => format!("{}d", s / 24 / 60 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(bytes(12), "12 B");
+        assert_eq!(bytes(2048), "2.0 KiB");
+        assert_eq!(bytes(5 * 1024 * 1024 + 1024 * 512), "5.5 MiB");
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age(100, 90), "10s");
+        assert_eq!(age(7201, 1), "2h");
+        assert_eq!(age(3 * 86400 + 5, 1), "3d");
+        assert_eq!(age(0, 100), "0s");
+        assert_eq!(age(100, 0), "?");
+    }
+}
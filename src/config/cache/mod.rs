@@ -1,12 +1,96 @@
 use crate::config::helio::Helio;
+use crate::error::{ErrorLevel, LightError};
+use crate::util::data::get_hash;
+use crate::util::hyperstr::{GRAY, LIGHT_RED, RESET};
 use std::collections::HashMap;
-use std::fs::{read_dir, File};
-use std::io::Read;
-use std::path::Path;
+use std::fs;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod command;
+pub mod variables;
+
+/// The extension of the metadata files stored next to the cached art.
+const META_EXTENSION: &str = "meta";
+
+/// Everything we know about a cached image besides the art itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Meta {
+    /// The path of the image the art was rendered from.
+    pub source: String,
+    /// The render parameters, see `ImageBuilder::params`.
+    pub params: String,
+    /// When the entry was created, in seconds since the epoch.
+    pub created: u64,
+    /// When the entry was last used, in seconds since the epoch.
+    pub accessed: u64,
+}
+
+impl Meta {
+    /// Create the metadata for a new cache entry.
+    ///
+    /// # Arguments:
+    /// * `source` - The path of the image.
+    /// * `params` - The render parameters.
+    ///
+    /// # Returns:
+    /// A new `Meta` instance, created and accessed right now.
+    ///
+    pub fn new(source: &str, params: &str) -> Self {
+        let now = now();
+        Self {
+            source: source.to_string(),
+            params: params.to_string(),
+            created: now,
+            accessed: now,
+        }
+    }
+
+    /// Parse the contents of a metadata file.
+    /// Unknown keys are ignored and missing keys keep their default value.
+    ///
+    /// # Arguments:
+    /// * `text` - The contents of the file.
+    ///
+    /// # Returns:
+    /// The parsed `Meta`.
+    ///
+    pub fn parse(text: &str) -> Self {
+        let mut meta = Self::default();
+        for (key, value) in text.lines().filter_map(|l| l.split_once(" = ")) {
+            match key {
+                "source" => meta.source = value.to_string(),
+                "params" => meta.params = value.to_string(),
+                "created" => meta.created = value.parse().unwrap_or(0),
+                "accessed" => meta.accessed = value.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+        meta
+    }
+
+    /// Turn the metadata into the contents of a metadata file.
+    pub fn serialize(&self) -> String {
+        format!(
+            "source = {}\nparams = {}\ncreated = {}\naccessed = {}\n",
+            self.source, self.params, self.created, self.accessed
+        )
+    }
+}
+
+/// The current time in seconds since the epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[allow(dead_code)]
 pub struct Image {
     pub data: String,
     pub hash: String,
+    pub meta: Meta,
 }
 
 impl Image {
@@ -14,20 +98,21 @@ impl Image {
     ///
     /// # Arguments:
     /// * `data` - The Art converted into an ascii String.
-    /// * `hash` - A "hash" of the image.
+    /// * `hash` - The hash of the image, see `util::data::get_hash`.
+    /// * `meta` - The metadata of the entry.
     ///
     /// # Returns:
     /// A new `Image` instance.
     ///
     /// # Example:
     /// ```
-    /// use config::cache::Image;
+    /// use config::cache::{Image, Meta};
     ///
-    /// let image = Image::new("data".to_string(), "hash".to_string());
+    /// let image = Image::new("data".to_string(), "hash".to_string(), Meta::default());
     /// ```
     ///
-    pub fn new(data: String, hash: String) -> Image {
-        Image { data, hash }
+    pub fn new(data: String, hash: String, meta: Meta) -> Image {
+        Image { data, hash, meta }
     }
 
     /// Get the image data.
@@ -39,7 +124,7 @@ impl Image {
     /// ```
     /// use config::cache::Image;
     ///
-    /// let image = Image::new("data".to_string(), "hash".to_string());
+    /// let image = Image::new("data".to_string(), "hash".to_string(), Meta::default());
     /// assert_eq!(image.get_data(), "data");
     /// ```
     ///
@@ -48,15 +133,31 @@ impl Image {
     }
 }
 
+/// An image in the cache, as listed by `Cache::entries`.
+pub struct Entry {
+    pub hash: String,
+    pub meta: Meta,
+    /// The size of the art in bytes.
+    pub size: u64,
+}
+
+/// The name of the index file in the cache directory.
+const INDEX: &str = "index.cache";
+
+/// When an entry was created and last used, in seconds since the epoch.
+type Times = (u64, u64);
+
+#[allow(dead_code)]
 pub struct Cache {
     pub config: Helio,
-    pub images: HashMap<String, Image>,
-    pub size: usize,
+    pub dir: PathBuf,
+    index: Option<HashMap<String, Times>>,
 }
 
 impl Cache {
-    /// Create a new cache instance and add existing images to the cache.
-    /// The Images are stored with their hash as key.
+    /// Create a new cache instance for a cache directory.
+    /// Nothing is read until an entry is actually needed, the art is stored in a file named after its hash
+    /// and the metadata lives next to it in `<hash>.meta`.
     ///
     /// # Arguments
     /// * `config` - A `Helio` Config instance.
@@ -66,41 +167,20 @@ impl Cache {
     /// # Example:
     /// ```
     /// use helio::config::cache::Cache;
-    /// let cache = Cache::new();
+    /// let cache = Cache::new(config, "~/.config/lightfetch/cache");
     /// ```
     ///
     pub fn new(config: Helio, dir: &str) -> Self {
-        let mut size: usize = 0;
-        let cache_dir = Path::new(dir);
-        let mut images = HashMap::new();
-        if cache_dir.is_dir() {
-            if let Ok(read_dir) = read_dir(cache_dir) {
-                for entry in read_dir.filter_map(Result::ok) {
-                    if let Ok(mut file) = File::open(entry.path()) {
-                        let mut data = String::new();
-                        file.read_to_string(&mut data).unwrap();
-                        let hash = entry
-                            .path()
-                            .file_name()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .to_string();
-                        images.insert(hash.clone(), Image::new(data, hash));
-                        size += 1;
-                    }
-                }
-            };
-        }
-
         Self {
-            size,
-            images,
             config,
+            dir: PathBuf::from(dir),
+            index: None,
         }
     }
 
     /// Get a specific image from the cache by its hash.
+    /// Marks the image as used, so it doesn't get evicted any time soon.
+    /// Corrupt entries are removed and treated as missing.
     ///
     /// # Arguments:
     /// * `hash` - A `String` containing the hash of the image.
@@ -111,12 +191,30 @@ impl Cache {
     /// # Example:
     /// ```
     /// use helio::config::cache::Cache;
-    /// let cache = Cache::new();
-    /// let image = cache.get_image("hash".to_string());
+    /// let mut cache = Cache::new(config, "~/.config/lightfetch/cache");
+    /// let image = cache.get("hash".to_string());
     /// ```
     ///
-    pub fn get(&self, hash: String) -> Option<&Image> {
-        self.images.get(hash.as_str())
+    pub fn get(&mut self, hash: String) -> Option<Image> {
+        let bytes = fs::read(self.dir.join(&hash)).ok()?;
+        let data = match String::from_utf8(bytes) {
+            Ok(data) => data,
+            Err(_) => {
+                self.remove(&hash);
+                return None;
+            }
+        };
+        // Entries from older versions don't have any metadata, they are the first to go.
+        let mut meta = fs::read_to_string(self.meta_path(&hash))
+            .map(|m| Meta::parse(&m))
+            .unwrap_or_default();
+        meta.accessed = now();
+        // Failing to update the access time isn't worth bothering the user.
+        let _ = fs::write(self.meta_path(&hash), meta.serialize());
+        self.index()
+            .insert(hash.clone(), (meta.created, meta.accessed));
+        let _ = self.save_index();
+        Some(Image::new(data, hash, meta))
     }
 
     /// Check if the cache contains a image with the given hash.
@@ -130,14 +228,428 @@ impl Cache {
     /// # Example:
     /// ```
     /// use helio::config::cache::Cache;
-    /// let cache = Cache::new();
-    /// let contains = cache.contains("hash".to_string());
+    /// let cache = Cache::new(config, "~/.config/lightfetch/cache");
+    /// let contains = cache.exists("hash".to_string());
     /// ```
     ///
+    #[allow(dead_code)]
     pub fn exists(&self, hash: String) -> bool {
-        self.images.contains_key(hash.as_str())
+        self.dir.join(hash).is_file()
+    }
+
+    /// Add an image to the cache and write it to the cache directory.
+    /// Evicts the least recently used images afterwards, see `evict`.
+    ///
+    /// # Arguments:
+    /// * `hash` - A `String` containing the hash of the image.
+    /// * `data` - The rendered art.
+    /// * `meta` - The metadata of the entry.
+    ///
+    /// # Returns:
+    /// A `LightError` warning if the cache couldn't be written.
+    ///
+    /// # Example:
+    /// ```
+    /// use helio::config::cache::{Cache, Meta};
+    /// let mut cache = Cache::new(config, "~/.config/lightfetch/cache");
+    /// cache.insert("hash".to_string(), art, Meta::new("image.png", "size=15"))?;
+    /// ```
+    ///
+    pub fn insert(&mut self, hash: String, data: String, meta: Meta) -> Result<(), LightError> {
+        let write = || -> std::io::Result<()> {
+            fs::create_dir_all(&self.dir)?;
+            fs::write(self.dir.join(&hash), &data)?;
+            fs::write(self.meta_path(&hash), meta.serialize())
+        };
+        if let Err(why) = write() {
+            return Err(self.error(why));
+        }
+        self.index().insert(hash, (meta.created, meta.accessed));
+        self.evict();
+        self.save_index().map_err(|why| self.error(why))
+    }
+
+    /// Delete the least recently used images if there are more than `[CACHE] ^ delete oldest if more than`.
+    /// Only does something if `[CACHE] clear it?` is enabled.
+    ///
+    /// # Returns:
+    /// The hashes of the deleted images.
+    ///
+    pub fn evict(&mut self) -> Vec<String> {
+        let enabled = self.config.get_bool("CACHE", "clear it?").unwrap_or(false);
+        let max = self
+            .config
+            .get_int("CACHE", "^ delete oldest if more than")
+            .unwrap_or(0) as usize;
+        let index = self.index();
+        if !enabled || max == 0 || index.len() <= max {
+            return Vec::new();
+        }
+
+        let mut entries: Vec<(&String, &Times)> = index.iter().collect();
+        entries.sort_by_key(|(hash, (created, accessed))| (*accessed, *created, hash.to_string()));
+        let evicted: Vec<String> = entries[..entries.len() - max]
+            .iter()
+            .map(|(hash, _)| hash.to_string())
+            .collect();
+
+        for hash in &evicted {
+            self.remove(hash);
+        }
+        evicted
+    }
+
+    /// Get the number of images in the cache.
+    #[allow(dead_code)]
+    pub fn len(&mut self) -> usize {
+        self.index().len()
+    }
+
+    /// Get every image in the cache, without reading the art itself.
+    ///
+    /// # Returns:
+    /// The entries, the least recently used first.
+    ///
+    pub fn entries(&mut self) -> Vec<Entry> {
+        let mut hashes: Vec<String> = self.index().keys().cloned().collect();
+        hashes.sort();
+        let mut entries: Vec<Entry> = hashes
+            .into_iter()
+            .filter_map(|hash| {
+                let size = fs::metadata(self.dir.join(&hash)).ok()?.len();
+                let meta = fs::read_to_string(self.meta_path(&hash))
+                    .map(|m| Meta::parse(&m))
+                    .unwrap_or_default();
+                Some(Entry { hash, meta, size })
+            })
+            .collect();
+        entries.sort_by_key(|e| (e.meta.accessed, e.meta.created));
+        entries
+    }
+
+    /// Delete every image in the cache.
+    ///
+    /// # Returns:
+    /// The number of deleted images.
+    ///
+    pub fn clear(&mut self) -> usize {
+        // Entries that aren't in the index anymore are deleted as well.
+        let mut hashes: Vec<String> = self.scan().into_keys().collect();
+        hashes.extend(self.index().keys().cloned());
+        hashes.sort();
+        hashes.dedup();
+        for hash in &hashes {
+            self.remove(hash);
+        }
+        let _ = fs::remove_file(self.dir.join(INDEX));
+        self.index = None;
+        hashes.len()
     }
+
+    /// Delete the images that can't be used anymore and evict the least recently used ones.
+    /// That's every image whose source changed or is gone, or whose metadata is broken.
+    ///
+    /// # Returns:
+    /// The hashes of the deleted images.
+    ///
+    pub fn prune(&mut self) -> Vec<String> {
+        // Rebuild the index, so entries that went missing are dropped.
+        self.index = Some(self.scan());
+        let mut pruned: Vec<String> = self
+            .entries()
+            .into_iter()
+            .filter(|e| {
+                e.meta.source.is_empty()
+                    || get_hash(Path::new(&e.meta.source), &e.meta.params)
+                        .map_or(true, |hash| hash != e.hash)
+            })
+            .map(|e| e.hash)
+            .collect();
+        for hash in &pruned {
+            self.remove(hash);
+        }
+        pruned.extend(self.evict());
+        let _ = self.save_index();
+        pruned
+    }
+
+    /// Delete an image and its metadata.
+    fn remove(&mut self, hash: &str) {
+        let _ = fs::remove_file(self.dir.join(hash));
+        let _ = fs::remove_file(self.meta_path(hash));
+        self.index().remove(hash);
+    }
+
+    /// Get the index of all entries, it's loaded the first time it's needed.
+    /// If the index file is missing or broken, it's rebuilt from the metadata files.
+    fn index(&mut self) -> &mut HashMap<String, Times> {
+        if self.index.is_none() {
+            let index = fs::read_to_string(self.dir.join(INDEX))
+                .ok()
+                .and_then(|text| parse_index(&text))
+                .unwrap_or_else(|| self.scan());
+            self.index = Some(index);
+        }
+        self.index.get_or_insert_with(HashMap::new)
+    }
+
+    /// Build the index by looking at every entry in the cache directory.
+    /// Only the small metadata files are read, files without one aren't ours and are left alone.
+    fn scan(&self) -> HashMap<String, Times> {
+        let mut index = HashMap::new();
+        let read_dir = match read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(_) => return index,
+        };
+        for entry in read_dir.filter_map(Result::ok) {
+            let path = entry.path();
+            let hash = match path.file_name().and_then(|n| n.to_str()) {
+                Some(hash) if is_hash(hash) && path.is_file() => hash,
+                _ => continue,
+            };
+            if let Ok(meta) = fs::read_to_string(self.meta_path(hash)) {
+                let meta = Meta::parse(&meta);
+                index.insert(hash.to_string(), (meta.created, meta.accessed));
+            }
+        }
+        index
+    }
+
+    /// Write the index file, if it was loaded.
+    fn save_index(&self) -> std::io::Result<()> {
+        let index = match &self.index {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let mut hashes: Vec<&String> = index.keys().collect();
+        hashes.sort();
+        let text: String = hashes
+            .iter()
+            .map(|hash| {
+                let (created, accessed) = index[*hash];
+                format!("{} = {} {}\n", hash, created, accessed)
+            })
+            .collect();
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(INDEX), text)
+    }
+
+    /// The path of the metadata file of an image.
+    fn meta_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", hash, META_EXTENSION))
+    }
+
+    /// Create the warning for a failed write.
+    fn error(&self, why: std::io::Error) -> LightError {
+        LightError::new(
+            format!(
+                "Could not write to the cache at {}{}{}: {}{}{}",
+                GRAY,
+                self.dir.display(),
+                RESET,
+                LIGHT_RED,
+                why,
+                RESET
+            ),
+            ErrorLevel::Warning,
+        )
+    }
+}
+
+/// Check if a file name is a hash in the format of `util::data::get_hash`, 16 lowercase hex digits.
+fn is_hash(name: &str) -> bool {
+    name.len() == 16 && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Parse the index file, every line looks like `<hash> = <created> <accessed>`.
+///
+/// # Arguments:
+/// * `text` - The contents of the index file.
+///
+/// # Returns:
+/// The index, `None` if any line is broken.
+///
+fn parse_index(text: &str) -> Option<HashMap<String, Times>> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let (hash, times) = line.split_once(" = ")?;
+            if !is_hash(hash) {
+                return None;
+            }
+            let (created, accessed) = times.split_once(' ')?;
+            Some((
+                hash.to_string(),
+                (created.parse().ok()?, accessed.parse().ok()?),
+            ))
+        })
+        .collect()
 }
 
 #[cfg(test)]
-pub(crate) mod cache_tests {}
+pub(crate) mod cache_tests {
+    use super::*;
+    use std::env;
+
+    fn config(max: u32) -> Helio {
+        let mut config = Helio::new();
+        config.set_str("CACHE", "clear it?", Some("true"));
+        config.set_str(
+            "CACHE",
+            "^ delete oldest if more than",
+            Some(&max.to_string()),
+        );
+        config
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("lightfetch_cache_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// A name in the format of `get_hash`.
+    fn hash(n: u64) -> String {
+        format!("{:016x}", n)
+    }
+
+    fn meta(accessed: u64) -> Meta {
+        Meta {
+            source: "image.png".to_string(),
+            params: "size=15".to_string(),
+            created: accessed,
+            accessed,
+        }
+    }
+
+    #[test]
+    fn test_meta() {
+        let meta = meta(42);
+        assert_eq!(Meta::parse(&meta.serialize()), meta);
+        assert_eq!(Meta::parse("garbage"), Meta::default());
+    }
+
+    #[test]
+    fn test_insert() {
+        let dir = temp_dir("insert");
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        cache
+            .insert(hash(0xabc), "art".to_string(), meta(1))
+            .unwrap();
+        assert!(dir.join(hash(0xabc)).is_file());
+        assert!(dir.join(format!("{}.meta", hash(0xabc))).is_file());
+        assert!(dir.join(INDEX).is_file());
+
+        // A new instance finds the entry and its metadata.
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        assert!(cache.exists(hash(0xabc)));
+        assert_eq!(cache.len(), 1);
+        let image = cache.get(hash(0xabc)).unwrap();
+        assert_eq!(image.get_data(), "art");
+        assert_eq!(image.meta.source, "image.png");
+        assert!(image.meta.accessed > 1);
+        assert!(cache.get(hash(1)).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let dir = temp_dir("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(hash(0xabc)), [0xff, 0xfe, 0x00]).unwrap();
+        fs::write(dir.join(format!("{}.meta", hash(0xabc))), "").unwrap();
+        fs::write(dir.join(INDEX), "garbage").unwrap();
+
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        // The broken index gets rebuilt from the directory.
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(hash(0xabc)).is_none());
+        assert!(!dir.join(hash(0xabc)).exists());
+        assert_eq!(cache.len(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_evict() {
+        let dir = temp_dir("evict");
+        let mut cache = Cache::new(config(2), dir.to_str().unwrap());
+        for (n, accessed) in [(0xa, 3), (0xb, 1), (0xc, 2)] {
+            cache.insert(hash(n), hash(n), meta(accessed)).unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.exists(hash(0xb)));
+        assert!(!dir.join(format!("{}.meta", hash(0xb))).exists());
+
+        // Using an entry keeps it around, also for the next instance.
+        let mut cache = Cache::new(config(2), dir.to_str().unwrap());
+        cache.get(hash(0xc));
+        cache.insert(hash(0xd), hash(0xd), meta(4)).unwrap();
+        assert!(cache.exists(hash(0xc)));
+        assert!(!cache.exists(hash(0xa)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = temp_dir("prune");
+        let source = dir.join("image.txt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&source, "image").unwrap();
+        let current = get_hash(&source, "size=15").unwrap();
+
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        let mut valid = meta(1);
+        valid.source = source.to_str().unwrap().to_string();
+        cache
+            .insert(current.clone(), "art".to_string(), valid)
+            .unwrap();
+        cache.insert(hash(2), "art".to_string(), meta(2)).unwrap();
+
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        assert_eq!(cache.prune(), vec![hash(2)]);
+        let entries = cache.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].hash, current);
+        assert_eq!(entries[0].size, 3);
+
+        assert_eq!(cache.clear(), 1);
+        assert_eq!(cache.len(), 0);
+        assert!(source.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_evict_disabled() {
+        let dir = temp_dir("disabled");
+        let mut config = config(1);
+        config.set_str("CACHE", "clear it?", Some("false"));
+        let mut cache = Cache::new(config, dir.to_str().unwrap());
+        for n in [0xa, 0xb] {
+            cache.insert(hash(n), hash(n), meta(1)).unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_foreign_files() {
+        let dir = temp_dir("foreign");
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        cache.insert(hash(1), "art".to_string(), meta(1)).unwrap();
+        // Not named like a hash, or without the metadata of an entry.
+        let foreign = ["notes", "ABCDEF0123456789", "0123456789abcdef0", &hash(2)];
+        for name in foreign {
+            fs::write(dir.join(name), "mine").unwrap();
+        }
+        fs::remove_file(dir.join(INDEX)).unwrap();
+
+        let mut cache = Cache::new(config(20), dir.to_str().unwrap());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.clear(), 1);
+        assert!(!dir.join(hash(1)).exists());
+        for name in foreign {
+            assert!(dir.join(name).is_file(), "{} was deleted", name);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use crate::config::cache::now;
+use crate::error::{ErrorLevel, LightError};
+use crate::fetch::packages;
+use crate::util::hyperstr::{GRAY, LIGHT_RED, RESET};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// The name of the file in the cache directory.
+const FILE_NAME: &str = "variables.cache";
+
+/// Files that change when the cached values change, besides the package databases, e.g. after a release upgrade.
+const WATCHED: [&str; 1] = ["/etc/os-release"];
+
+/// Persists the values that barely change between runs, so they don't have to be fetched every time.
+/// They are thrown away after `[CACHE] recache variables if older than x hours` or once the system changed.
+pub struct Variables {
+    pub path: PathBuf,
+    pub ttl: u64,
+}
+
+impl Variables {
+    /// Create a new `Variables` cache.
+    ///
+    /// # Arguments:
+    /// * `dir` - The cache directory.
+    /// * `hours` - How long the values stay valid, can be a fraction like 0.5, 0 to always fetch them.
+    ///
+    /// # Returns:
+    /// A new `Variables` instance.
+    ///
+    pub fn new(dir: &Path, hours: f64) -> Self {
+        Self {
+            path: dir.join(FILE_NAME),
+            ttl: (hours.max(0.0) * 60.0 * 60.0) as u64,
+        }
+    }
+
+    /// Load the cached values if they are still valid.
+    ///
+    /// # Returns:
+    /// The values by their key, `None` if they have to be fetched again.
+    ///
+    pub fn load(&self) -> Option<HashMap<String, String>> {
+        self.load_with(now(), &stamp())
+    }
+
+    fn load_with(&self, now: u64, stamp: &str) -> Option<HashMap<String, String>> {
+        let text = fs::read_to_string(&self.path).ok()?;
+        let mut values: HashMap<String, String> = text
+            .lines()
+            .filter_map(|l| l.split_once(" = "))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        let created: u64 = values.remove("created")?.parse().ok()?;
+        if values.remove("stamp")? != stamp || now.saturating_sub(created) >= self.ttl {
+            return None;
+        }
+        Some(values)
+    }
+
+    /// Get the time the values were cached at.
+    ///
+    /// # Returns:
+    /// The time in seconds since the epoch, `None` if nothing is cached.
+    ///
+    pub fn created(&self) -> Option<u64> {
+        fs::read_to_string(&self.path)
+            .ok()?
+            .lines()
+            .find_map(|l| l.strip_prefix("created = "))?
+            .parse()
+            .ok()
+    }
+
+    /// Delete the cached values.
+    ///
+    /// # Returns:
+    /// True if there was something to delete.
+    ///
+    pub fn clear(&self) -> bool {
+        fs::remove_file(&self.path).is_ok()
+    }
+
+    /// Write the values to the cache directory.
+    ///
+    /// # Arguments:
+    /// * `values` - The values by their key.
+    ///
+    /// # Returns:
+    /// A `LightError` warning if the cache couldn't be written.
+    ///
+    pub fn store(&self, values: &HashMap<String, String>) -> Result<(), LightError> {
+        self.store_with(values, now(), &stamp())
+    }
+
+    fn store_with(
+        &self,
+        values: &HashMap<String, String>,
+        now: u64,
+        stamp: &str,
+    ) -> Result<(), LightError> {
+        let mut text = format!("created = {}\nstamp = {}\n", now, stamp);
+        let mut keys: Vec<&String> = values.keys().collect();
+        keys.sort();
+        for key in keys {
+            // Every value has to stay on its line.
+            text.push_str(&format!("{} = {}\n", key, values[key].replace('\n', " ")));
+        }
+
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&self.path, &text)
+        };
+        write().map_err(|why| {
+            LightError::new(
+                format!(
+                    "Could not write the variable cache to {}{}{}: {}{}{}",
+                    GRAY,
+                    self.path.display(),
+                    RESET,
+                    LIGHT_RED,
+                    why,
+                    RESET
+                ),
+                ErrorLevel::Warning,
+            )
+        })
+    }
+}
+
+/// Describe the state of the system the cached values depend on.
+/// The modification times of the `WATCHED` files, the package databases and the running kernel.
+///
+/// # Returns:
+/// The stamp as a String, it changes whenever the cached values might have.
+///
+pub fn stamp() -> String {
+    let home = std::env::var("HOME").unwrap_or_default();
+    let mut stamp = fs::read_to_string("/proc/sys/kernel/osrelease")
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let databases = packages::databases(Path::new("/"), Path::new(&home));
+    let watched = WATCHED
+        .iter()
+        .map(PathBuf::from)
+        .chain(databases.into_iter().flatten());
+    for path in watched {
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        stamp.push_str(&format!(";{}", modified));
+    }
+    stamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_variables() {
+        let dir = env::temp_dir().join("lightfetch_variables");
+        let _ = fs::remove_dir_all(&dir);
+        let variables = Variables::new(&dir, 1.0);
+        assert_eq!(variables.load_with(100, "a"), None);
+
+        let mut values = HashMap::new();
+        values.insert("distro.name".to_string(), "Arch Linux".to_string());
+        values.insert("cpu.flags".to_string(), "fpu\nvme".to_string());
+        variables.store_with(&values, 100, "a").unwrap();
+
+        let loaded = variables.load_with(100 + 3599, "a").unwrap();
+        assert_eq!(loaded["distro.name"], "Arch Linux");
+        assert_eq!(loaded["cpu.flags"], "fpu vme");
+        assert_eq!(loaded.len(), 2);
+
+        // Expired.
+        assert_eq!(variables.load_with(100 + 3600, "a"), None);
+        // The system changed.
+        assert_eq!(variables.load_with(100, "b"), None);
+
+        assert_eq!(Variables::new(&dir, 0.5).ttl, 1800);
+
+        assert_eq!(variables.created(), Some(100));
+        assert!(variables.clear());
+        assert_eq!(variables.created(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,321 @@
+use crate::config::helio::schema::Issue;
+use crate::config::helio::toml::{self, Item};
+use crate::config::helio::{self, Helio};
+use crate::error::{ErrorLevel, LightError};
+use crate::util::hyperstr::{GRAY, GREEN, LIGHT_RED, RESET};
+use crate::variable::creator::Creator;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// The placeholders of a mount start with this, e.g. `{DISK_/home}`. The mount point depends on the machine.
+const DISK_PREFIX: &str = "DISK_";
+/// The values of a mount, e.g. `{DISK_ROOT_USED}`, see `FetchData::set_disks`.
+const DISK_SUFFIXES: [&str; 4] = ["_USED", "_TOTAL", "_PERCENT", "_FSTYPE"];
+
+/// Check a config without running the fetch, for `--check-config`.
+/// Validates it against the schema and renders the fetch text to find unknown placeholders and broken case tags.
+/// The config is never created or changed.
+///
+/// # Arguments:
+/// * `path`: The path to the config.
+/// * `variables`: The values of all placeholders, dummy values are fine.
+///
+/// # Returns:
+/// Every issue, a `LightError` if the config can't be read at all.
+///
+pub(crate) fn check_config(
+    path: &Path,
+    variables: &HashMap<&str, &str>,
+) -> Result<Vec<Issue>, LightError> {
+    let mut config = Helio::new();
+    let mut issues = config.read(path)?;
+    // The text comes from the last file that sets it, an override or an included theme.
+    let (file, template) = config
+        .files()
+        .iter()
+        .rev()
+        .map(|file| {
+            let input = fs::read_to_string(file).unwrap_or_default();
+            let template = match toml::is_toml(file) {
+                true => Template::from_toml(&input),
+                false => Template::new(&input),
+            };
+            (file.clone(), template)
+        })
+        .find(|(_, template)| !template.lines.is_empty())
+        .unwrap_or_else(|| (path.to_path_buf(), Template::new("")));
+
+    if config
+        .get_bool("GENERAL", "enable variables")
+        .unwrap_or(true)
+    {
+        let prefix = config.get_str("GENERAL", "^ prefix")?;
+        let suffix = config.get_str("GENERAL", "^ suffix")?;
+        let creator = Creator::init(&template.text, &prefix, &suffix);
+        for placeholder in creator
+            .unknown_variables(variables)
+            .into_iter()
+            .filter(|placeholder| !is_disk_variable(placeholder))
+        {
+            let (line, column) = template.position(offset(&template.text, placeholder));
+            issues.push(Issue {
+                file: Some(file.clone()),
+                line,
+                column,
+                message: format!(
+                    "Unknown placeholder {LIGHT_RED}'{}'{RESET} in {GRAY}[FETCH] text{RESET}",
+                    placeholder
+                ),
+                level: ErrorLevel::Warning,
+            });
+        }
+    }
+
+    if config
+        .get_bool("GENERAL", "enable case variables")
+        .unwrap_or(true)
+    {
+        let prefix = config.get_str("GENERAL", "^ case prefix")?;
+        let suffix = config.get_str("GENERAL", "^ case suffix")?;
+        for letter in ["^ uppercase letter", "^ lowercase letter"] {
+            let letter = config.get_str("GENERAL", letter)?;
+            let start = prefix.replace("{letter}", &letter);
+            let end = suffix.replace("{letter}", &letter);
+            for at in Creator::unbalanced(&template.text, &start, &end) {
+                let tag = if template.text[at..].starts_with(&end) {
+                    &end
+                } else {
+                    &start
+                };
+                let (line, column) = template.position(at);
+                issues.push(Issue {
+                    file: Some(file.clone()),
+                    line,
+                    column,
+                    message: format!(
+                        "Case tag {LIGHT_RED}'{}'{RESET} is never {} {GRAY}| Tags look like '{}text{}' and can't be nested{RESET}",
+                        tag,
+                        if tag == &end { "opened" } else { "closed" },
+                        start,
+                        end
+                    ),
+                    level: ErrorLevel::Warning,
+                });
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    Ok(issues)
+}
+
+/// Check if a placeholder is one of the variables of a mount, the mount itself isn't checked.
+/// The mount is `ROOT` or a path, e.g. `{DISK_/home_PERCENT}`, and the value one of `DISK_SUFFIXES`.
+fn is_disk_variable(placeholder: &str) -> bool {
+    let placeholder = placeholder.to_uppercase();
+    let name = match placeholder.strip_prefix(DISK_PREFIX) {
+        Some(name) => name,
+        None => return false,
+    };
+    let mount = DISK_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name);
+    mount == "ROOT" || mount.starts_with('/')
+}
+
+/// Print the issues of `check_config`.
+///
+/// # Arguments:
+/// * `path`: The path to the config.
+/// * `issues`: The issues.
+///
+pub(crate) fn report(path: &Path, issues: &[Issue]) {
+    if issues.is_empty() {
+        println!("{GREEN}{} is valid.{RESET}", path.display());
+        return;
+    }
+    for issue in issues {
+        eprintln!(
+            "{GRAY}{}:{}:{}:{RESET} {}",
+            issue.file.as_deref().unwrap_or(path).display(),
+            issue.line,
+            issue.column,
+            issue.message
+        );
+    }
+    eprintln!("{LIGHT_RED}Found {} issue(s).{RESET}", issues.len());
+}
+
+/// The byte position of a slice in the text it was taken from.
+fn offset(text: &str, part: &str) -> usize {
+    part.as_ptr() as usize - text.as_ptr() as usize
+}
+
+/// The `[FETCH] text` as it is in the file, so positions can be traced back to it.
+struct Template {
+    /// The lines of the text, joined the same way `Helio` does it.
+    text: String,
+    /// Where each line starts in `text`, with its line and column in the file.
+    lines: Vec<(usize, usize, usize)>,
+}
+
+impl Template {
+    fn new(input: &str) -> Self {
+        let mut template = Self {
+            text: String::new(),
+            lines: Vec::new(),
+        };
+        let mut section = String::new();
+        let mut in_text = false;
+
+        for (num, raw_line) in input.lines().enumerate() {
+            let line = helio::strip_comment(raw_line);
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let (Some(0), Some(end)) = (trimmed.find('['), trimmed.rfind(']')) {
+                section = trimmed[1..end].trim().to_string();
+                in_text = false;
+                continue;
+            }
+            let part = if line.starts_with(char::is_whitespace) {
+                if !in_text {
+                    continue;
+                }
+                trimmed
+            } else {
+                in_text = false;
+                match trimmed.split_once('=') {
+                    Some((key, value)) if section == "FETCH" && key.trim() == "text" => {
+                        in_text = true;
+                        value.trim()
+                    }
+                    _ => continue,
+                }
+            };
+
+            if !template.lines.is_empty() {
+                template.text.push('\n');
+            }
+            let column = raw_line[..offset(raw_line, part)].chars().count() + 1;
+            template.lines.push((template.text.len(), num + 1, column));
+            template.text.push_str(part);
+        }
+        template
+    }
+
+    /// Same as `new`, for TOML configs.
+    fn from_toml(input: &str) -> Self {
+        let mut template = Self {
+            text: String::new(),
+            lines: Vec::new(),
+        };
+        let text = toml::parse(input, "default")
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .find_map(|item| match item {
+                Item::Key {
+                    section,
+                    key,
+                    value,
+                    lines,
+                    ..
+                } if section == "FETCH" && key == "text" => Some((value, lines)),
+                _ => None,
+            });
+        if let Some((value, lines)) = text {
+            for (part, (line, column)) in value.split('\n').zip(lines) {
+                if !template.lines.is_empty() {
+                    template.text.push('\n');
+                }
+                template.lines.push((template.text.len(), line, column));
+                template.text.push_str(part);
+            }
+        }
+        template
+    }
+
+    /// Get the line and column in the file of a byte position in `text`.
+    fn position(&self, at: usize) -> (usize, usize) {
+        let &(start, line, column) = self
+            .lines
+            .iter()
+            .rev()
+            .find(|(start, _, _)| *start <= at)
+            .unwrap_or(&(0, 0, 1));
+        (line, column + self.text[start..at].chars().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::hyperstr::Ansi;
+    use std::env;
+
+    #[test]
+    fn test_template() {
+        let template = Template::new("[FETCH]\ntext = {a} # b\n\t  {c}\ngap = 1\n  {d}\n");
+        assert_eq!(template.text, "{a}\n{c}");
+        assert_eq!(template.position(0), (2, 8));
+        assert_eq!(template.position(4), (3, 4));
+    }
+
+    #[test]
+    fn test_template_toml() {
+        let template = Template::from_toml("[FETCH]\ntext = [\n  \"{a}\",\n  '{c}',\n]\n");
+        assert_eq!(template.text, "{a}\n{c}");
+        assert_eq!(template.position(0), (3, 4));
+        assert_eq!(template.position(4), (4, 4));
+    }
+
+    #[test]
+    fn test_check_config() {
+        let path = env::temp_dir().join("lightfetch_check.ini");
+        fs::write(
+            &path,
+            "[GENERAL]\nauto center = maybe\n[FETCH]\ntext = {USERNAME} [U]{nope}\n  x [/L] {disk_/home_PERCENT} {DISK_ROOT_USD}\n",
+        )
+        .unwrap();
+        let variables = HashMap::from([("USERNAME", "bwte")]);
+        let issues: Vec<(usize, usize, String)> = check_config(&path, &variables)
+            .unwrap()
+            .into_iter()
+            .map(|i| (i.line, i.column, i.message.strip_ansi_colors()))
+            .collect();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(issues.len(), 5);
+        assert_eq!(issues[0].0, 2);
+        assert_eq!(
+            issues[1],
+            (
+                4,
+                19,
+                "Case tag '[U]' is never closed | Tags look like '[U]text[/U]' and can't be nested"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            issues[2],
+            (
+                4,
+                23,
+                "Unknown placeholder 'nope' in [FETCH] text".to_string()
+            )
+        );
+        assert_eq!(issues[3].0, 5);
+        assert!(issues[3].2.starts_with("Case tag '[/L]' is never opened"));
+        assert_eq!(
+            issues[4].2,
+            "Unknown placeholder 'DISK_ROOT_USD' in [FETCH] text"
+        );
+        assert!(is_disk_variable("disk_root"));
+        assert!(is_disk_variable("DISK_/mnt/my_disk_FSTYPE"));
+        assert!(!is_disk_variable("DISK_HOME_USED"));
+    }
+}
@@ -6,6 +6,8 @@
 ///
 pub(crate) const DEFAULT: &str = r#"# Lightfetch config file.
 # Automatically generated, you can change the path with the --config [PATH] launch argument.
+# Keys from /etc/lightfetch/config.ini are loaded first, config.<hostname>.ini next to this file overrides them.
+# Use "include = path/to/file.ini" to load another file at that position, e.g. a shared theme.
 
 [ GENERAL ]
 # Should we center the art and fetchtext to each other?
@@ -58,7 +60,7 @@ text = {white}╭─────────────────────
     │ {blue}shell {gray}~ {white}{SHELL} {fill} │
     │ {blue}term {gray}~ {white}{TERMINAL} {fill} │
 	│ {blue}pkgs {gray}~ {white}{PACKAGES} {fill} │
-	│ {blue}cpu {gray}~ {white}[L]{cpu_model}[/L] {fill} │
+	│ {cpu_color}cpu {gray}~ {white}[L]{cpu_model}[/L] {fill} │
     │ {white}omg dynamic 😳 blazing fast 🔥 {fill} │
     │ {white}no dependencies 😶 open source 🥵 {fill} │
 	╰───────────────────────────────────────────╯{ignore}
@@ -80,10 +82,20 @@ right padding = 0
 # What art mode do you want to use?
 # More advanced settings are found in their respective section.
 # Too speed image mode up, i recommend enabling caching.
-# Valid options: ascii / image.
+# "image" draws with colored half blocks, "kitty" uses the kitty graphics protocol for the full resolution.
+# "iterm" uses the inline images of iTerm2 and WezTerm, "sixel" works on xterm, foot, mlterm and more.
+# "characters" converts the image to plain characters, for terminals without any of those.
+# "auto" picks the best image mode your terminal supports.
+# Valid options: ascii / auto / image / kitty / iterm / sixel / characters.
 # Default = ascii
 mode = image
 
+# Should "auto" ask the terminal what it supports? Otherwise only the environment variables are checked.
+# The terminal has to answer within the timeout in milliseconds.
+# Default = true, 100
+^ query terminal = true
+^ query timeout = 100
+
 # The art that should be displayed here.
 # Syntax is the same as the fetch-text. [Formatting && Variables] supported!
 # Formatting syntax needs to be correct, otherwise any file type allowed.
@@ -106,28 +118,85 @@ filter = Gaussian
 # Default = 15
 size = 15
 
+# How many colors should the image use? Fewer colors work on more terminals.
+# "mono" draws the image without any colors.
+# Options: truecolor, 256, 16, mono.
+# Default = truecolor
+color depth = truecolor
+
+# Hide the steps between the colors if the color depth isn't truecolor.
+# Options: none, floyd-steinberg, ordered.
+# Default = floyd-steinberg
+^ dither = floyd-steinberg
+
+# The characters used by the "characters" art mode, from dark to bright.
+# Default = " .:-=+*%@"
+characters = " .:-=+*%@"
+
+# Draw edges with line characters? ( | / - \ )
+# Default = false
+^ edges = false
+
+# Color the characters with the color of the image?
+# Options: none, 16, 256, truecolor.
+# Default = none
+^ tint = none
+
 [ ADVANCED MODE ]
 
 # TODO!
 
+[ CPU ]
+# Usage: {CPU_USAGE}% of all cores, {CPU_USAGE_CORES} for each core, e.g. "12% 3% 40% 7%".
+# The load averages of the last 1, 5 and 15 minutes: {LOADAVG_1}, {LOADAVG_5}, {LOADAVG_15}.
+
+# How long to measure the cpu usage in milliseconds, it's only measured if the fetch text shows it.
+# Set to 0 to disable.
+# Default = 200
+sample interval = 200
+
+# The color of {CPU_COLOR}, to color everything about the cpu at once.
+# A name like light_red, rgb(r, g, b), a number from 0 to 255 or a hex color like '#rrggbb' in double quotes.
+# Default = blue
+color = blue
+
 [ MEMORY ]
-# Number rounding strength.
+# Usage: {MEMORY_USED} / {MEMORY_TOTAL} ({MEMORY_PERCENT}%), the same for {SWAP_USED} and so on.
+# Also available: {MEMORY_FREE}, {MEMORY_AVAILABLE} and {SWAP_FREE}.
+
+# Number of decimal places.
 # Default = 2
 rounding = 2
 
+# Count in steps of 1000 (KB, MB, GB, TB) or 1024 (KiB, MiB, GiB, TiB)?
+# Options: decimal, binary.
+# Default = decimal
+units = decimal
+
 # Below you are able to change the formatting of the size values.
-# Default = " KB", " MB", " GB"
+# Left at the default, binary units use " KiB", " MiB", " GiB", " TiB" instead.
+# Default = " KB", " MB", " GB", " TB"
 kb = " KB"
 mb = " MB"
 gb = " GB"
+tb = " TB"
+
+# The color of {MEMORY_COLOR}, the same options as in [CPU].
+# Default = blue
+color = blue
 
 [ UPTIME ]
-# Should we use a custom suffix?
-# Example: [0 {DAY}s]
+# Usage: {UPTIME} -> 2 days 3 hours 4 minutes.
+# The parts on their own: {UPTIME_DAYS}, {UPTIME_HOURS}, {UPTIME_MINUTES}, {UPTIME_SECONDS}.
+# Padded to two digits: {UPTIME_HOURS_PADDED}, {UPTIME_MINUTES_PADDED}, {UPTIME_SECONDS_PADDED}.
+# The seconds since boot: {UPTIME_RAW}.
+
+# Should we add the units below to the values?
+# Otherwise the uptime looks like a clock, e.g. 2:03:04:05.
 # Default: true
 suffix = true
 
-# Add plurals if value is more than 1?
+# Add plurals if value isn't 1? Short units like " d" never get one.
 # Default = true
 ^ plurals = true
  
@@ -135,15 +204,39 @@ suffix = true
 # Default = "s"
 ^ character = "s"
 
+# Leave out the units that are 0?
+# Default = true
 hide if zero = true
 
 # Change the formatting of the units here.
 # Also determine the gap with spaces.
-# Default = " d", " h", " h", " s"
-day = " d"
-hour = " h"
-minute = " m"
-second = " s"
+# Default = " day", " hour", " minute", " second"
+day = " day"
+hour = " hour"
+minute = " minute"
+second = " second"
+
+# The color of {UPTIME_COLOR}, the same options as in [CPU].
+# Default = blue
+color = blue
+
+[ DISK ]
+# Usage: {DISK} -> /: 45.20 GB / 100.00 GB (45%), for all mounts below.
+# Each mount has {DISK_ROOT} for "/" and {DISK_/home} for "/home", with _USED, _TOTAL, _PERCENT and _FSTYPE, e.g. {DISK_/home_PERCENT}.
+# The sizes use the settings of [MEMORY].
+
+# Which mounts should we show? Separate them with commas, "auto" shows every disk.
+# Default = /
+show = /
+
+# Filesystem types that aren't disks, they are never shown.
+# Network filesystems are hidden too, the fetch would hang if their server doesn't answer.
+# Default = tmpfs, devtmpfs, ramfs, overlay, squashfs, proc, sysfs, ..., nfs, nfs4, cifs, ...
+hide types = tmpfs, devtmpfs, ramfs, overlay, squashfs, proc, sysfs, cgroup, cgroup2, devpts, mqueue, hugetlbfs, debugfs, tracefs, securityfs, pstore, bpf, configfs, fusectl, autofs, binfmt_misc, efivarfs, nsfs, nfs, nfs4, cifs, smb3, smbfs, 9p, afs, ceph, glusterfs, davfs, fuse.sshfs, fuse.rclone
+
+# The color of {DISK_COLOR}, the same options as in [CPU].
+# Default = blue
+color = blue
 
 [ CACHE ]
 # EXPERIMENTAL SETTINGS!
@@ -171,6 +264,7 @@ clear it? = true
  		
 # Delete caches of variables after an x amount of time.
 # After that it will get the values like usual and cache it again.
+# Fractions like 0.5 work too.
 # Default = 24
 recache variables if older than x hours = 24
 
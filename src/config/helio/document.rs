@@ -0,0 +1,444 @@
+use super::strip_comment;
+/// A part of a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Comments, empty lines and anything else that is kept as it is.
+    Text(String),
+    /// A section header, e.g. `[ GENERAL ]`.
+    Section { name: String, line: String },
+    /// A key with its value, indented lines continue the value.
+    Entry { key: String, lines: Vec<String> },
+}
+
+/// A key of a config together with the comments directly above it, see `Document::blocks`.
+pub(crate) struct Block {
+    pub key: String,
+    pub comments: Vec<String>,
+    /// The line of the key and the lines that continue its value.
+    pub lines: Vec<String>,
+}
+
+/// A config file as it is written, so keys can be changed without losing comments or the layout.
+/// Comments, empty lines, the order of the keys, `^` sub keys and indented multi-line values are kept,
+/// a document that isn't changed is written back exactly as it was read.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Document {
+    nodes: Vec<Node>,
+    /// If the file ended with a line break.
+    newline: bool,
+}
+
+impl Document {
+    /// Parse a config the same way `Helio::parse` reads it.
+    ///
+    /// # Arguments:
+    /// * `input`: The contents of the config.
+    ///
+    /// # Returns:
+    /// The document.
+    ///
+    pub fn parse(input: &str) -> Self {
+        let mut nodes: Vec<Node> = Vec::new();
+        // Comments and empty lines inside a multi-line value, they only belong to it if it continues after them.
+        let mut pending: Vec<String> = Vec::new();
+        let mut in_entry = false;
+
+        for line in input.lines() {
+            let content = strip_comment(line);
+            let trimmed = content.trim();
+
+            if trimmed.is_empty() {
+                if in_entry {
+                    pending.push(line.to_string());
+                } else {
+                    nodes.push(Node::Text(line.to_string()));
+                }
+                continue;
+            }
+
+            if content.starts_with(char::is_whitespace) && in_entry {
+                if let Some(Node::Entry { lines, .. }) = nodes.last_mut() {
+                    lines.append(&mut pending);
+                    lines.push(line.to_string());
+                }
+                continue;
+            }
+
+            nodes.extend(pending.drain(..).map(Node::Text));
+            if let (Some(0), Some(end)) = (trimmed.find('['), trimmed.rfind(']')) {
+                nodes.push(Node::Section {
+                    name: trimmed[1..end].trim().to_string(),
+                    line: line.to_string(),
+                });
+                in_entry = false;
+                continue;
+            }
+
+            let key = trimmed.split('=').next().unwrap_or_default().trim();
+            nodes.push(Node::Entry {
+                key: key.to_string(),
+                lines: vec![line.to_string()],
+            });
+            in_entry = true;
+        }
+        nodes.extend(pending.into_iter().map(Node::Text));
+
+        Self {
+            nodes,
+            newline: input.is_empty() || input.ends_with('\n'),
+        }
+    }
+
+    /// Get the value of a key, multi-line values are joined with `\n`.
+    ///
+    /// # Arguments:
+    /// * `section`: The section of the key.
+    /// * `key`: The key.
+    ///
+    /// # Returns:
+    /// The value, `None` if the key doesn't exist. Keys without a `=` have an empty value.
+    ///
+    pub fn get(&self, section: &str, key: &str) -> Option<String> {
+        let index = self.find(section, key)?;
+        match &self.nodes[index] {
+            Node::Entry { lines, .. } => {
+                let mut values = lines
+                    .iter()
+                    .map(|line| strip_comment(line).trim())
+                    .filter(|line| !line.is_empty());
+                let first = values.next().unwrap_or_default();
+                let first = first.split_once('=').map_or("", |(_, value)| value.trim());
+                Some(
+                    std::iter::once(first)
+                        .chain(values)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Set the value of a key.
+    /// Existing keys keep their spelling, the spacing around the `=` and their comment.
+    /// New keys are added after the last key of their section, new sections at the end of the file.
+    ///
+    /// # Arguments:
+    /// * `section`: The section of the key.
+    /// * `key`: The key.
+    /// * `value`: The value, lines after the first are indented. `None` writes the key without a value.
+    ///
+    pub fn set(&mut self, section: &str, key: &str, value: Option<&str>) {
+        if let Some(index) = self.find(section, key) {
+            if let Node::Entry { lines, .. } = &mut self.nodes[index] {
+                *lines = rewrite(lines, key, value);
+            }
+            return;
+        }
+
+        let entry = Node::Entry {
+            key: key.to_string(),
+            lines: rewrite(&[], key, value),
+        };
+        match self.section_range(section) {
+            Some((start, end)) => {
+                // After the last key, so the comments of the next section stay with it.
+                let at = self.nodes[start..end]
+                    .iter()
+                    .rposition(|node| matches!(node, Node::Entry { .. } | Node::Section { .. }))
+                    .map_or(end, |i| start + i + 1);
+                self.nodes.insert(at, entry);
+            }
+            None => {
+                if self
+                    .nodes
+                    .last()
+                    .is_some_and(|node| *node != Node::Text(String::new()))
+                {
+                    self.nodes.push(Node::Text(String::new()));
+                }
+                self.nodes.push(Node::Section {
+                    name: section.to_string(),
+                    line: format!("[ {} ]", section),
+                });
+                self.nodes.push(entry);
+            }
+        }
+    }
+
+    /// The names of the sections, in the order of the file.
+    pub(crate) fn sections(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Section { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The keys of a section, in the order of the file.
+    /// Comments belong to the key below them, an empty line in between separates them.
+    pub(crate) fn blocks(&self, section: &str) -> Vec<Block> {
+        let (start, end) = match self.section_range(section) {
+            Some(range) => range,
+            None => return Vec::new(),
+        };
+        let mut blocks = Vec::new();
+        let mut comments = Vec::new();
+        for node in &self.nodes[start..end] {
+            match node {
+                Node::Text(line) if line.trim().is_empty() => comments.clear(),
+                Node::Text(line) => comments.push(line.clone()),
+                Node::Entry { key, lines } => blocks.push(Block {
+                    key: key.clone(),
+                    comments: std::mem::take(&mut comments),
+                    lines: lines.clone(),
+                }),
+                Node::Section { .. } => {}
+            }
+        }
+        blocks
+    }
+
+    /// The lines of a section, from its header to the last line that isn't empty.
+    pub(crate) fn section_lines(&self, section: &str) -> Vec<String> {
+        let (start, end) = match self.section_range(section) {
+            Some(range) => range,
+            None => return Vec::new(),
+        };
+        // Sections with a name start at their header.
+        let start = match start.checked_sub(1).map(|i| &self.nodes[i]) {
+            Some(Node::Section { name, .. }) if name == section => start - 1,
+            _ => start,
+        };
+        let end = self.content_end(start, end);
+        Document {
+            nodes: self.nodes[start..end].to_vec(),
+            newline: false,
+        }
+        .to_string()
+        .lines()
+        .map(String::from)
+        .collect()
+    }
+
+    /// Insert lines into a section, they are parsed like the rest of the config.
+    ///
+    /// # Arguments:
+    /// * `section`: The section, it has to exist.
+    /// * `after`: The key to insert the lines after, `None` for after the last line of the section that isn't empty.
+    /// * `lines`: The lines to insert.
+    ///
+    pub(crate) fn insert(&mut self, section: &str, after: Option<&str>, lines: &[String]) {
+        let at = match after.and_then(|key| self.find(section, key)) {
+            Some(index) => index + 1,
+            None => match self.section_range(section) {
+                Some((start, end)) => self.content_end(start, end),
+                None => return,
+            },
+        };
+        let nodes = Document::parse(&lines.join("\n")).nodes;
+        self.nodes.splice(at..at, nodes);
+    }
+
+    /// Add lines after the last line of the file that isn't empty, they are parsed like the rest of the config.
+    pub(crate) fn append(&mut self, lines: &[String]) {
+        let end = self.content_end(0, self.nodes.len());
+        self.nodes.truncate(end);
+        self.nodes.extend(Document::parse(&lines.join("\n")).nodes);
+    }
+
+    /// The position after the last node between `start` and `end` that isn't an empty line.
+    fn content_end(&self, start: usize, end: usize) -> usize {
+        self.nodes[start..end]
+            .iter()
+            .rposition(|node| !matches!(node, Node::Text(line) if line.trim().is_empty()))
+            .map_or(start, |i| start + i + 1)
+    }
+
+    /// Find the node of a key.
+    fn find(&self, section: &str, key: &str) -> Option<usize> {
+        let (start, end) = self.section_range(section)?;
+        (start..end).find(|&i| matches!(&self.nodes[i], Node::Entry { key: k, .. } if k == key))
+    }
+
+    /// The nodes of a section, without its header. Keys before the first header are in the section `default`.
+    fn section_range(&self, section: &str) -> Option<(usize, usize)> {
+        let mut current = "default";
+        let mut range: Option<(usize, usize)> = None;
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Node::Section { name, .. } = node {
+                current = name;
+                if range.is_some() {
+                    break;
+                }
+                if current == section {
+                    range = Some((i + 1, i + 1));
+                }
+                continue;
+            }
+            match &mut range {
+                Some((_, end)) => *end = i + 1,
+                None if current == section => range = Some((i, i + 1)),
+                None => {}
+            }
+        }
+        if range.is_none() && section == "default" {
+            return Some((0, 0));
+        }
+        range
+    }
+}
+
+impl std::fmt::Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let lines: Vec<&str> = self
+            .nodes
+            .iter()
+            .flat_map(|node| match node {
+                Node::Text(line) | Node::Section { line, .. } => vec![line.as_str()],
+                Node::Entry { lines, .. } => lines.iter().map(String::as_str).collect(),
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))?;
+        if self.newline && !lines.is_empty() {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the lines of a key with a new value, keeping what can be kept of the old lines.
+fn rewrite(old: &[String], key: &str, value: Option<&str>) -> Vec<String> {
+    let first = old.first().map_or("", String::as_str);
+    let content = strip_comment(first);
+    // The comment after the value, with the spacing in front of it.
+    let comment = &first[content.trim_end().len()..];
+    let comment = if comment.trim().is_empty() {
+        ""
+    } else {
+        comment
+    };
+
+    let value = match value {
+        Some(value) => value,
+        None => {
+            let key = match content.split_once('=') {
+                Some((key, _)) => key.trim_end(),
+                None if !content.trim().is_empty() => content.trim_end(),
+                None => key,
+            };
+            return vec![format!("{}{}", key, comment)];
+        }
+    };
+    let prefix = match content.find('=') {
+        Some(i) => {
+            let spacing = content[i + 1..].len() - content[i + 1..].trim_start().len();
+            let spacing = if content[i + 1..].trim().is_empty() {
+                1
+            } else {
+                spacing
+            };
+            format!("{}{}", &content[..=i], " ".repeat(spacing))
+        }
+        None => format!("{} = ", key),
+    };
+    // Continued lines keep the indentation of the first old one.
+    let indent = old
+        .iter()
+        .skip(1)
+        .find(|line| !strip_comment(line).trim().is_empty())
+        .map_or("    ", |line| &line[..line.len() - line.trim_start().len()]);
+
+    // A `#` would start a comment, quotes keep it in the value.
+    let mut values = value.lines().map(|line| {
+        let line = line.trim_end_matches('\r');
+        match strip_comment(line).len() < line.len() {
+            true => format!("\"{}\"", line),
+            false => line.to_string(),
+        }
+    });
+    let mut lines = vec![format!(
+        "{}{}{}",
+        prefix,
+        values.next().unwrap_or_default(),
+        comment
+    )];
+    lines.extend(values.map(|line| format!("{}{}", indent, line.trim())));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# Header.
+
+[ GENERAL ]
+# Center?
+auto center  =  true # yes
+^ prefix = {
+
+[ FETCH ]
+text = a
+\tb # comment
+
+    c
+# Next.
+gap = 2
+";
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(Document::parse(CONFIG).to_string(), CONFIG);
+        let without_newline = CONFIG.trim_end();
+        assert_eq!(
+            Document::parse(without_newline).to_string(),
+            without_newline
+        );
+        let default = super::super::default::DEFAULT;
+        assert_eq!(Document::parse(default).to_string(), default);
+    }
+
+    #[test]
+    fn test_get() {
+        let document = Document::parse(CONFIG);
+        assert_eq!(document.get("GENERAL", "auto center").unwrap(), "true");
+        assert_eq!(document.get("GENERAL", "^ prefix").unwrap(), "{");
+        assert_eq!(document.get("FETCH", "text").unwrap(), "a\nb\nc");
+        assert_eq!(document.get("FETCH", "auto center"), None);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut document = Document::parse(CONFIG);
+        document.set("GENERAL", "auto center", Some("false"));
+        document.set("GENERAL", "^ suffix", Some("}"));
+        document.set("FETCH", "text", Some("x\r\ny"));
+        document.set("CACHE", "enable", Some("false"));
+        document.set("CACHE", "color", Some("#ff8800"));
+        assert_eq!(
+            document.to_string(),
+            "# Header.
+
+[ GENERAL ]
+# Center?
+auto center  =  false # yes
+^ prefix = {
+^ suffix = }
+
+[ FETCH ]
+text = x
+\ty
+# Next.
+gap = 2
+
+[ CACHE ]
+enable = false
+color = \"#ff8800\"
+"
+        );
+        assert_eq!(document.get("FETCH", "text").unwrap(), "x\ny");
+    }
+}
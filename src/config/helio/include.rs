@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The config shared by all users of the machine, it is loaded before the one of the user.
+pub const SYSTEM_CONFIG: &str = "/etc/lightfetch/config.ini";
+/// The key that loads another config at its position, e.g. `include = ~/themes/base.ini`.
+pub const INCLUDE: &str = "include";
+
+/// Get the files a config is made of, later files override the keys of earlier ones:
+/// the system config, the config itself and the override for this host, e.g. `config.archlinux.ini`.
+///
+/// # Arguments:
+/// * `path`: The path to the config.
+/// * `system`: The path to the system config.
+/// * `host`: The hostname of this machine.
+///
+/// # Returns:
+/// The files that exist, the config itself is always part of them if there is no system config.
+///
+pub(crate) fn layers(path: &Path, system: &Path, host: Option<&str>) -> Vec<PathBuf> {
+    let mut layers = Vec::new();
+    if system.is_file() && system != path {
+        layers.push(system.to_path_buf());
+    }
+    if path.exists() || layers.is_empty() {
+        layers.push(path.to_path_buf());
+    }
+    if let Some(host) = host.and_then(|host| host_config(path, host)) {
+        if host.is_file() {
+            layers.push(host);
+        }
+    }
+    layers
+}
+
+/// Get the path of the override for a host, `config.ini` becomes `config.<host>.ini`.
+fn host_config(path: &Path, host: &str) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_str()?;
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{}.{}.{}", stem, host, extension),
+        None => format!("{}.{}", stem, host),
+    };
+    Some(path.with_file_name(name))
+}
+
+/// Get the hostname of this machine.
+pub(crate) fn hostname() -> Option<String> {
+    ["/etc/hostname", "/proc/sys/kernel/hostname"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|host| host.trim().to_string())
+        .find(|host| !host.is_empty())
+}
+
+/// Get the path of an included file, relative paths start at the directory of the config including it.
+///
+/// # Arguments:
+/// * `value`: The value of the `include` key.
+/// * `dir`: The directory of the config including it.
+/// * `home`: The home directory, for paths starting with `~`.
+///
+pub(crate) fn resolve(value: &str, dir: &Path, home: Option<&str>) -> PathBuf {
+    let value = value.replace('"', "");
+    let value = match (value.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) => format!("{}{}", home, rest),
+        _ => value,
+    };
+    dir.join(value)
+}
+
+/// Check if a config includes other files.
+pub(crate) fn includes(input: &str) -> bool {
+    input.lines().any(|line| {
+        let line = super::strip_comment(line);
+        !line.starts_with(char::is_whitespace)
+            && line
+                .split_once('=')
+                .is_some_and(|(key, _)| key.trim() == INCLUDE)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_layers() {
+        let dir = env::temp_dir().join("lightfetch_layers");
+        fs::create_dir_all(&dir).unwrap();
+        let system = dir.join("system.ini");
+        let user = dir.join("config.ini");
+        let host = dir.join("config.box.ini");
+        let _ = fs::remove_file(&system);
+        let _ = fs::remove_file(&host);
+        fs::write(&user, "").unwrap();
+
+        assert_eq!(layers(&user, &system, Some("box")), vec![user.clone()]);
+        fs::write(&system, "").unwrap();
+        fs::write(&host, "").unwrap();
+        assert_eq!(
+            layers(&user, &system, Some("box")),
+            vec![system.clone(), user.clone(), host.clone()]
+        );
+        assert_eq!(
+            layers(&user, &system, None),
+            vec![system.clone(), user.clone()]
+        );
+        fs::remove_file(&user).unwrap();
+        assert_eq!(layers(&user, &system, None), vec![system.clone()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_includes() {
+        assert!(includes("[GENERAL]\ninclude = base.ini\n"));
+        assert!(!includes(
+            "# include = base.ini\n[FETCH]\ntext = a\n  include = b\n"
+        ));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dir = Path::new("/home/bwte/.config/lightfetch");
+        assert_eq!(
+            resolve("\"themes/base.ini\"", dir, None),
+            dir.join("themes/base.ini")
+        );
+        assert_eq!(
+            resolve("/etc/base.ini", dir, None),
+            PathBuf::from("/etc/base.ini")
+        );
+        assert_eq!(
+            resolve("~/base.ini", dir, Some("/home/bwte")),
+            PathBuf::from("/home/bwte/base.ini")
+        );
+    }
+}
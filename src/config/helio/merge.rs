@@ -0,0 +1,131 @@
+use super::document::Document;
+
+/// Add the keys of the default config that are missing in the user config.
+/// The user config is kept as it is, missing keys are added with their comments after the key
+/// that comes before them in the default config, or at the end of their section.
+/// Missing sections are added at the end of the file.
+/// Both configs are read by `Document`, the same way `Helio::parse` reads them.
+///
+/// # Arguments:
+/// * `user`: The contents of the user config.
+/// * `default`: The contents of the default config.
+///
+/// # Returns:
+/// The merged config, `None` if nothing is missing.
+///
+pub(crate) fn merge(user: &str, default: &str) -> Option<String> {
+    let mut document = Document::parse(user);
+    let default = Document::parse(default);
+    let mut changed = false;
+
+    for section in default.sections() {
+        if !document.sections().contains(&section) {
+            let mut lines = vec![String::new()];
+            lines.extend(default.section_lines(section));
+            document.append(&lines);
+            changed = true;
+            continue;
+        }
+        // Missing keys are added after the last key that exists.
+        let mut anchor: Option<String> = None;
+        let mut missing: Vec<String> = Vec::new();
+        for block in default.blocks(section) {
+            if document.get(section, &block.key).is_some() {
+                if !missing.is_empty() {
+                    document.insert(section, anchor.as_deref(), &missing);
+                    missing.clear();
+                    changed = true;
+                }
+                anchor = Some(block.key);
+                continue;
+            }
+            // Sub keys without comments stick to their parent.
+            if !block.comments.is_empty() {
+                missing.push(String::new());
+            }
+            missing.extend(block.comments);
+            missing.extend(block.lines);
+        }
+        if !missing.is_empty() {
+            document.insert(section, anchor.as_deref(), &missing);
+            changed = true;
+        }
+    }
+
+    changed.then(|| {
+        let merged = document.to_string();
+        match merged.ends_with('\n') {
+            true => merged,
+            false => merged + "\n",
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: &str = "# Header.
+
+[ GENERAL ]
+# Center?
+auto center = true
+
+# Gap.
+gap = 2
+^ sub = 1
+
+[ CACHE ]
+# Enable?
+enable = true
+";
+
+    #[test]
+    fn test_merge() {
+        let user = "[ GENERAL ]\nauto center = false\n\n";
+        let merged = merge(user, DEFAULT).unwrap();
+        assert_eq!(
+            merged,
+            "[ GENERAL ]
+auto center = false
+
+# Gap.
+gap = 2
+^ sub = 1
+
+[ CACHE ]
+# Enable?
+enable = true
+"
+        );
+        assert_eq!(merge(&merged, DEFAULT), None);
+    }
+
+    #[test]
+    fn test_merge_keeps_custom() {
+        let user = "[ CACHE ]\nenable = false\n# mine\n\n[ GENERAL ]\ngap = 5\n";
+        let merged = merge(user, DEFAULT).unwrap();
+        assert_eq!(
+            merged,
+            "[ CACHE ]\nenable = false\n# mine\n\n[ GENERAL ]\ngap = 5\n^ sub = 1\n\n# Center?\nauto center = true\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_multi_line() {
+        // The empty line belongs to the value, it continues after it.
+        let user = "[ GENERAL ]\ngap = a\n\n    b\nauto center = true\n";
+        let merged = merge(user, DEFAULT).unwrap();
+        assert_eq!(
+            merged,
+            "[ GENERAL ]\ngap = a\n\n    b\n^ sub = 1\nauto center = true\n\n[ CACHE ]\n# Enable?\nenable = true\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_complete() {
+        assert_eq!(merge(DEFAULT, DEFAULT), None);
+        let default = super::super::default::DEFAULT;
+        assert_eq!(merge(default, default), None);
+    }
+}
@@ -1,15 +1,24 @@
 mod default;
+pub mod document;
+mod include;
+mod merge;
+mod overrides;
+pub mod schema;
+pub mod toml;
 
 use std::collections::HashMap as Map;
 
 use crate::error::{ErrorLevel, LightError};
+use crate::image::dither::Dither;
+use crate::util::color::{Color, ColorDepth};
 use crate::util::hyperstr::N;
 use crate::util::hyperstr::{GRAY, GREEN, LIGHT_RED, RED, RESET};
+use crate::util::mth::Units;
 use image::imageops::FilterType;
 use std::collections::HashMap;
 use std::convert::AsRef;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// # The `Helio` struct simply contains a nested hashmap of the loaded configuration and the default symbols.
 /// ## Example:
@@ -25,6 +34,20 @@ pub struct Helio {
     comment: char,
     delimit: char,
     bool: HashMap<bool, &'static str>,
+    files: Vec<PathBuf>,
+    /// The keys changed with `set` since the config was read, these are written by `save`.
+    edits: Vec<(String, String)>,
+}
+
+/// Everything read while loading a config and the files it is made of.
+#[derive(Default)]
+struct Layers {
+    map: Map<String, Map<String, Option<String>>>,
+    issues: Vec<schema::Issue>,
+    /// The files that were read, in the order they were finished.
+    files: Vec<PathBuf>,
+    /// The files that are being read, to catch includes that loop.
+    stack: Vec<PathBuf>,
 }
 
 impl Helio {
@@ -35,6 +58,8 @@ impl Helio {
             comment: '#',
             delimit: '=',
             bool: HashMap::from([(true, "true"), (false, "false")]),
+            files: Vec::new(),
+            edits: Vec::new(),
         }
     }
 
@@ -42,42 +67,130 @@ impl Helio {
         &mut self,
         path: T,
     ) -> Result<Map<String, Map<String, Option<String>>>, LightError> {
-        self.create(path.as_ref())?;
-        /*if !path.as_ref().exists() { TODO: USE THIS FOR RELEASE!
-            self.create(path.as_ref())?;
-        }*/
-        self.map = match fs::read_to_string(&path) {
+        let system = Path::new(include::SYSTEM_CONFIG);
+        // With a system config the user config only needs the keys that are different.
+        if !path.as_ref().exists() {
+            if !system.is_file() {
+                self.create(path.as_ref())?;
+            }
+        } else if !system.is_file() {
+            self.update(path.as_ref())?;
+        }
+        // Unknown sections and keys are ignored, only values that can't be used stop the fetch.
+        let (errors, warnings): (Vec<schema::Issue>, Vec<schema::Issue>) = self
+            .read(path.as_ref())?
+            .into_iter()
+            .partition(|issue| issue.level == ErrorLevel::Config);
+        if !warnings.is_empty() {
+            eprint!(
+                "{}",
+                issues_error(path.as_ref(), &warnings, ErrorLevel::Warning)
+            );
+        }
+        if !errors.is_empty() {
+            return Err(issues_error(path.as_ref(), &errors, ErrorLevel::Config));
+        }
+
+        Ok(self.map.clone())
+    }
+
+    /// Read and validate a config, without creating or updating it like `load` does.
+    /// The system config, the config itself and the override for this host are read in that order,
+    /// later files override the keys of earlier ones. `include = path` reads another file at its position.
+    ///
+    /// # Arguments:
+    /// * `path`: The path to the config.
+    ///
+    /// # Returns:
+    /// The issues found by `schema::validate` in all files, the config is loaded either way.
+    ///
+    pub fn read<T: AsRef<Path>>(&mut self, path: T) -> Result<Vec<schema::Issue>, LightError> {
+        let host = include::hostname();
+        let mut layers = Layers::default();
+        for file in include::layers(
+            path.as_ref(),
+            Path::new(include::SYSTEM_CONFIG),
+            host.as_deref(),
+        ) {
+            self.read_file(&file, &mut layers)?;
+        }
+        self.map = layers.map;
+        self.files = layers.files;
+        self.edits.clear();
+        Ok(layers.issues)
+    }
+
+    /// Get the files the loaded config was read from, in the order they were read.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Read one file of a config and the files it includes on top of the layers read so far.
+    fn read_file(&self, path: &Path, layers: &mut Layers) -> Result<(), LightError> {
+        let input = match fs::read_to_string(path) {
             Err(why) => Err(LightError::new(
                 format!(
                     "Could not read config at {GRAY}'{RED}{}{GRAY}'{RESET}: {GRAY}{}",
-                    path.as_ref().display(),
+                    path.display(),
                     why
                 ),
                 ErrorLevel::Error,
             ))?,
-            Ok(s) => match self.parse(s) {
-                Err(why) => Err(LightError::new(
-                    format!(
-                        "Could not parse config at {GRAY}'{RED}{}{GRAY}'{RESET}: {GRAY}{}",
-                        path.as_ref().display(),
-                        why
-                    ),
-                    ErrorLevel::Error,
-                ))?,
-                Ok(map) => map,
-            },
+            Ok(s) => s,
         };
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if layers.stack.contains(&canonical) {
+            return Err(LightError::new(
+                format!(
+                    "Config {GRAY}'{RED}{}{GRAY}'{RESET} includes itself",
+                    path.display()
+                ),
+                ErrorLevel::Config,
+            ));
+        }
 
-        Ok(self.map.clone())
+        layers.stack.push(canonical);
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let issues = layers.issues.len();
+        let parsed = if toml::is_toml(path) {
+            self.parse_toml(&input, dir, layers)
+        } else {
+            layers.issues.extend(schema::validate(&input));
+            self.parse_into(&input, dir, layers)
+        };
+        for issue in &mut layers.issues[issues..] {
+            issue.file.get_or_insert_with(|| path.to_path_buf());
+        }
+        layers.stack.pop();
+        layers.files.push(path.to_path_buf());
+
+        parsed.map_err(|why| {
+            LightError::new(
+                format!(
+                    "Could not parse config at {GRAY}'{RED}{}{GRAY}'{RESET}: {GRAY}{}",
+                    path.display(),
+                    why.message
+                ),
+                ErrorLevel::Error,
+            )
+        })
     }
 
-    #[allow(dead_code)]
     pub fn create<T: AsRef<Path>>(
         &mut self,
         path: T,
     ) -> Result<Map<String, Map<String, Option<String>>>, LightError> {
         let default = default::DEFAULT.to_owned();
-        self.map = match fs::write(&path, default.clone()) {
+        if let Some(parent) = path.as_ref().parent() {
+            // The error shows up when writing the file.
+            let _ = fs::create_dir_all(parent);
+        }
+        // TOML configs start as the default config written in TOML.
+        let contents = match toml::is_toml(path.as_ref()) {
+            true => toml::from_ini(&default),
+            false => default.clone(),
+        };
+        self.map = match fs::write(&path, contents) {
             Err(why) => {
                 return Err(LightError::new(
                     format!("couldn't create {}: {}", &path.as_ref().display(), why),
@@ -97,15 +210,128 @@ impl Helio {
         Ok(self.map.clone())
     }
 
+    /// Add the keys that are missing in an older config from the default config.
+    /// The old config is kept as `<path>.bak` before it gets rewritten, the values of the user are never changed.
+    /// Configs that include other files and TOML configs are left alone.
+    ///
+    /// # Arguments:
+    /// * `path`: The path to the config.
+    ///
+    /// # Returns:
+    /// True if the config was rewritten.
+    ///
+    pub fn update<T: AsRef<Path>>(&self, path: T) -> Result<bool, LightError> {
+        let path = path.as_ref();
+        if toml::is_toml(path) {
+            return Ok(false);
+        }
+        // Unreadable configs are reported by `load`.
+        let user = match fs::read_to_string(path) {
+            Ok(user) => user,
+            Err(_) => return Ok(false),
+        };
+        // Configs built from other files only set the keys they change.
+        if include::includes(&user) {
+            return Ok(false);
+        }
+        let merged = match merge::merge(&user, default::DEFAULT) {
+            Some(merged) => merged,
+            None => return Ok(false),
+        };
+
+        rewrite(path, &merged, "Could not add the missing keys to")?;
+        Ok(true)
+    }
+
+    /// Write the keys changed with `set` to a config, everything else in the file stays as it is.
+    /// A config that doesn't exist yet starts as the default config, an existing one is kept as `<path>.bak`.
+    ///
+    /// # Arguments:
+    /// * `path`: The path to the config.
+    ///
+    /// # Returns:
+    /// A `LightError` if the config can't be written.
+    ///
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> Result<(), LightError> {
+        let path = path.as_ref();
+        if toml::is_toml(path) {
+            return Err(LightError::new(
+                format!(
+                    "Could not save the config to {GRAY}'{RED}{}{GRAY}'{RESET}: {GRAY}Only INI configs can be saved",
+                    path.display()
+                ),
+                ErrorLevel::Config,
+            ));
+        }
+        let input = fs::read_to_string(path).unwrap_or_else(|_| default::DEFAULT.to_owned());
+        let mut document = document::Document::parse(&input);
+        for (section, key) in &self.edits {
+            if let Some(value) = self.map.get(section).and_then(|keys| keys.get(key)) {
+                let value = value.as_ref().map(|value| value.replace(N, "\n"));
+                // Keys set to the value they already have keep their formatting.
+                if document.get(section, key) != Some(value.clone().unwrap_or_default()) {
+                    document.set(section, key, value.as_deref());
+                }
+            }
+        }
+        rewrite(path, &document.to_string(), "Could not save the config to")
+    }
+
+    /// Parse a TOML config into the layers read so far, reading included files where they are included.
+    ///
+    /// # Arguments:
+    /// * `input`: The contents of the config.
+    /// * `dir`: The directory of the config, included paths start there.
+    /// * `layers`: The layers read so far.
+    ///
+    fn parse_toml(&self, input: &str, dir: &Path, layers: &mut Layers) -> Result<(), LightError> {
+        let items = toml::parse(input, &self.default)?;
+        layers.issues.extend(toml::validate(&items));
+        for item in items {
+            if let toml::Item::Key {
+                section,
+                key,
+                value,
+                ..
+            } = item
+            {
+                if key == include::INCLUDE {
+                    let home = std::env::var("HOME").ok();
+                    self.read_file(&include::resolve(&value, dir, home.as_deref()), layers)?;
+                    continue;
+                }
+                let value = value.replace('\n', N);
+                layers
+                    .map
+                    .entry(section)
+                    .or_default()
+                    .insert(key, Some(value));
+            }
+        }
+        Ok(())
+    }
+
     fn parse(&self, input: String) -> Result<Map<String, Map<String, Option<String>>>, LightError> {
-        let mut map: Map<String, Map<String, Option<String>>> = Map::new();
+        let mut layers = Layers::default();
+        self.parse_into(&input, Path::new(""), &mut layers)?;
+        Ok(layers.map)
+    }
+
+    /// Parse a config into the layers read so far, reading included files where they are included.
+    ///
+    /// # Arguments:
+    /// * `input`: The contents of the config.
+    /// * `dir`: The directory of the config, included paths start there.
+    /// * `layers`: The layers read so far.
+    ///
+    fn parse_into(&self, input: &str, dir: &Path, layers: &mut Layers) -> Result<(), LightError> {
         let mut section = self.default.clone();
         let mut current_key: Option<String> = None;
 
         let out = |val: &str| val.to_owned();
 
         for (num, raw_line) in input.lines().enumerate() {
-            let line = match raw_line.find(|c: char| self.comment == c) {
+            let line = match find_comment(raw_line, self.comment) {
                 Some(idx) => &raw_line[..idx],
                 None => raw_line,
             };
@@ -142,7 +368,7 @@ impl Helio {
                     }
                 };
 
-                let value_map = map.entry(section.clone()).or_insert_with(Map::new);
+                let value_map = layers.map.entry(section.clone()).or_default();
 
                 let value = value_map
                     .entry(key.clone())
@@ -161,7 +387,17 @@ impl Helio {
                 continue;
             }
 
-            let value_map = map.entry(section.clone()).or_insert_with(Map::new);
+            if let Some((key, value)) = trimmed.split_once(self.delimit) {
+                if key.trim() == include::INCLUDE {
+                    let home = std::env::var("HOME").ok();
+                    let file = include::resolve(value.trim(), dir, home.as_deref());
+                    self.read_file(&file, layers)?;
+                    current_key = None;
+                    continue;
+                }
+            }
+
+            let value_map = layers.map.entry(section.clone()).or_default();
 
             match trimmed.find(&self.delimit.to_string()) {
                 Some(delimiter) => {
@@ -187,10 +423,16 @@ impl Helio {
             }
         }
 
-        Ok(map)
+        Ok(())
     }
 
     pub fn get_str(&self, section: &str, key: &str) -> Result<String, LightError> {
+        // Known keys that are missing fall back to their default.
+        if self.map.get(section).and_then(|x| x.get(key)).is_none() {
+            if let Some(schema) = schema::find(section, key) {
+                return Ok(schema.default.to_string());
+            }
+        }
         match self.map.get(section) {
             Some(x) => match x.get(key) {
                 Some(y) => match y {
@@ -218,57 +460,114 @@ impl Helio {
     }
 
     pub fn get_bool(&self, section: &str, key: &str) -> Result<bool, LightError> {
-        let value = self.get_str(section, key).unwrap();
-
-        match value.parse::<bool>() {
-            Ok(x) => Ok(x),
-            _ => {
-               Err(LightError::new(
-                    format!(
-                        "Section {GRAY}'{GREEN}{}{GRAY}'{RESET}: Key {GRAY}'{LIGHT_RED}{}{GRAY}'{RESET} is not a boolean",
-                        section, key
-                    ),
-                    ErrorLevel::Config,
-                ))
+        let value = self.get_str(section, key)?;
 
-            }
-        }
+        value
+            .parse::<bool>()
+            .map_err(|_| invalid(section, key, &value, "a boolean", "Use 'true' or 'false'"))
     }
 
     pub fn get_int(&self, section: &str, key: &str) -> Result<u32, LightError> {
-        let value = self.get_str(section, key).unwrap();
-
-        match value.parse::<u32>() {
-            Ok(x) => Ok(x),
-            _ => {
-                Err(LightError::new(
-                    format!(
-                        "Section {GRAY}'{GREEN}{}{GRAY}'{RESET}: Key {GRAY}'{LIGHT_RED}{}{GRAY}'{RESET} is not an integer",
-                        section, key
-                    ),
-                    ErrorLevel::Config,
-                ))
-            }
-        }
+        let value = self.get_str(section, key)?;
+
+        value.parse::<u32>().map_err(|_| {
+            invalid(
+                section,
+                key,
+                &value,
+                "a whole number",
+                "Use a number like '2'",
+            )
+        })
     }
 
-    pub fn get_filter(&self, section: &str, key: &str) -> Result<FilterType, LightError> {
-        let filter = self.get_str(section, key);
+    /// Get a number that can have decimal places.
+    ///
+    /// # Arguments:
+    /// * `section`: The section of the key.
+    /// * `key`: The key.
+    ///
+    /// # Returns:
+    /// The number, an error if it isn't a finite number.
+    ///
+    pub fn get_float(&self, section: &str, key: &str) -> Result<f64, LightError> {
+        let value = self.get_str(section, key)?;
+
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|x| x.is_finite())
+            .ok_or_else(|| invalid(section, key, &value, "a number", "Use a number like '1.5'"))
+    }
 
-        match filter.as_ref().unwrap().replace('"', "").as_str() {
-            "Nearest" => Ok(FilterType::Nearest),
-            "Gaussian" => Ok(FilterType::Gaussian),
-            "Triangle" => Ok(FilterType::Triangle),
-            "Catmull" => Ok(FilterType::CatmullRom),
-            "Lanczos" => Ok(FilterType::Lanczos3),
-            _ => Err(LightError::new(
-                format!(
-                    "Filter type {LIGHT_RED}'{}'{RESET} invalid {GRAY}| Available: 'Nearest', 'Gaussian', 'Triangle', 'Catmull', 'Lanczos'{RESET}",
-                    filter.unwrap()
-                ),
-                ErrorLevel::Config,
-            )),
+    /// Get a list, the values are split by commas or written on their own lines.
+    ///
+    /// # Arguments:
+    /// * `section`: The section of the key.
+    /// * `key`: The key.
+    ///
+    /// # Returns:
+    /// The values without the spaces around them, empty values are left out.
+    ///
+    pub fn get_list(&self, section: &str, key: &str) -> Result<Vec<String>, LightError> {
+        let value = self.get_str(section, key)?;
+
+        Ok(value
+            .split([',', '\n'])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    /// Get a color: a name like `light_red`, `"#rrggbb"`, `rgb(r, g, b)` or an index in the 256 color palette.
+    /// In INI configs the `#` of a hex color needs quotes, otherwise it starts a comment.
+    pub fn get_color(&self, section: &str, key: &str) -> Result<Color, LightError> {
+        let value = self.get_str(section, key)?;
+
+        Color::parse(&value).ok_or_else(|| {
+            invalid(
+                section,
+                key,
+                &value,
+                "a color",
+                "Use a name like 'light_red', '#rrggbb', 'rgb(r, g, b)' or a number from 0 to 255",
+            )
+        })
+    }
+
+    /// Get one of the values of a `ConfigEnum`, ignoring the case.
+    ///
+    /// # Arguments:
+    /// * `section`: The section of the key.
+    /// * `key`: The key.
+    ///
+    /// # Returns:
+    /// The value, a `LightError` with the available values if it's none of them.
+    ///
+    pub fn get_enum<T: ConfigEnum>(&self, section: &str, key: &str) -> Result<T, LightError> {
+        let value = self.get_str(section, key)?;
+
+        if let Some((_, x)) = T::VALUES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&value))
+        {
+            return Ok(x.clone());
         }
+        let names = T::VALUES.iter().map(|(name, _)| *name);
+        Err(invalid(
+            section,
+            key,
+            &format!("{}{}", value, schema::suggest(&value, names.clone())),
+            T::NAME,
+            &format!(
+                "Available: {}",
+                names
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ))
     }
 
     pub fn set(
@@ -277,7 +576,11 @@ impl Helio {
         key: &str,
         value: Option<String>,
     ) -> Option<Option<String>> {
-        match self.map.get_mut(&section.to_owned()) {
+        let edit = (section.to_owned(), key.to_owned());
+        if !self.edits.contains(&edit) {
+            self.edits.push(edit);
+        }
+        match self.map.get_mut(section) {
             Some(secondary) => secondary.insert(key.to_owned(), value),
             None => {
                 let mut value_map: Map<String, Option<String>> = Map::new();
@@ -297,3 +600,156 @@ impl Helio {
         self.set(section, key, value.map(String::from))
     }
 }
+
+/// Find where the comment of a line starts, a comment character between double quotes is part of the value.
+fn find_comment(line: &str, comment: char) -> Option<usize> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c == comment && !quoted => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Everything before the comment of a line, the same way `Helio::parse` does it.
+/// Values with a `#` need quotes, e.g. `color = "#ff8800"`.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    find_comment(line, '#').map_or(line, |i| &line[..i])
+}
+
+/// A setting with a fixed set of values, read with `Helio::get_enum`.
+pub trait ConfigEnum: Clone + 'static {
+    /// What a value is called in errors, e.g. `a filter`.
+    const NAME: &'static str;
+    /// The values as they are written in the config.
+    const VALUES: &'static [(&'static str, Self)];
+}
+
+impl ConfigEnum for FilterType {
+    const NAME: &'static str = "a filter";
+    const VALUES: &'static [(&'static str, Self)] = &[
+        ("Nearest", FilterType::Nearest),
+        ("Gaussian", FilterType::Gaussian),
+        ("Triangle", FilterType::Triangle),
+        ("Catmull", FilterType::CatmullRom),
+        ("Lanczos", FilterType::Lanczos3),
+    ];
+}
+
+impl ConfigEnum for ColorDepth {
+    const NAME: &'static str = "a color depth";
+    const VALUES: &'static [(&'static str, Self)] = &[
+        ("truecolor", ColorDepth::TrueColor),
+        ("256", ColorDepth::Ansi256),
+        ("16", ColorDepth::Ansi16),
+        ("none", ColorDepth::Mono),
+        ("mono", ColorDepth::Mono),
+    ];
+}
+
+impl ConfigEnum for Dither {
+    const NAME: &'static str = "a dithering";
+    const VALUES: &'static [(&'static str, Self)] = &[
+        ("none", Dither::None),
+        ("floyd-steinberg", Dither::FloydSteinberg),
+        ("ordered", Dither::Ordered),
+    ];
+}
+
+impl ConfigEnum for Units {
+    const NAME: &'static str = "a unit";
+    const VALUES: &'static [(&'static str, Self)] =
+        &[("binary", Units::Binary), ("decimal", Units::Decimal)];
+}
+
+/// The error for a value that isn't what a getter expects.
+///
+/// # Arguments:
+/// * `section`: The section of the key.
+/// * `key`: The key.
+/// * `value`: The value.
+/// * `expected`: What the value should be, e.g. `a boolean`.
+/// * `hint`: How to fix it.
+///
+fn invalid(section: &str, key: &str, value: &str, expected: &str, hint: &str) -> LightError {
+    LightError::new(
+        format!(
+            "Section {GRAY}'{GREEN}{}{GRAY}'{RESET}: Key {GRAY}'{LIGHT_RED}{}{GRAY}'{RESET} is not {}: {LIGHT_RED}'{}'{RESET} {GRAY}| {}{RESET}",
+            section, key, expected, value, hint
+        ),
+        ErrorLevel::Config,
+    )
+}
+
+/// Overwrite a config, the old one is kept as `<path>.bak` first.
+///
+/// # Arguments:
+/// * `path`: The path to the config.
+/// * `contents`: The new contents.
+/// * `failure`: The start of the error message, e.g. `Could not save the config to`.
+///
+/// # Returns:
+/// A `LightError` if the backup or the config can't be written.
+///
+fn rewrite(path: &Path, contents: &str, failure: &str) -> Result<(), LightError> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let written = match fs::read(path) {
+        Ok(old) => fs::write(&backup, old),
+        Err(_) => {
+            // The error shows up when writing the file.
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            Ok(())
+        }
+    };
+    written
+        .and_then(|_| fs::write(path, contents))
+        .map_err(|why| {
+            LightError::new(
+                format!(
+                    "{} {GRAY}'{RED}{}{GRAY}'{RESET}: {GRAY}{}",
+                    failure,
+                    path.display(),
+                    why
+                ),
+                ErrorLevel::Config,
+            )
+        })
+}
+
+/// The error for the issues `schema::validate` found in a config.
+///
+/// # Arguments:
+/// * `path`: The path to the config.
+/// * `issues`: The issues, of every layer and included file.
+/// * `level`: `Warning` for issues that are ignored, `Config` for the ones that stop the fetch.
+///
+fn issues_error(path: &Path, issues: &[schema::Issue], level: ErrorLevel) -> LightError {
+    let mut message = match level {
+        ErrorLevel::Warning => format!(
+            "Ignoring unknown settings in {GRAY}'{RED}{}{GRAY}'{RESET}:",
+            path.display()
+        ),
+        _ => format!(
+            "Invalid config at {GRAY}'{RED}{}{GRAY}'{RESET}:",
+            path.display()
+        ),
+    };
+    for issue in issues {
+        // Issues in other layers or included files name the file.
+        let file = match &issue.file {
+            Some(file) if file != path => format!("{}:", file.display()),
+            _ => String::new(),
+        };
+        message.push_str(&format!(
+            "\n{GRAY}{}{}:{}{RESET} {}",
+            file, issue.line, issue.column, issue.message
+        ));
+    }
+    LightError::new(message, level)
+}
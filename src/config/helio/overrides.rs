@@ -0,0 +1,172 @@
+use super::schema::{self, Key, SCHEMA};
+use super::Helio;
+use crate::error::{ErrorLevel, LightError};
+use crate::util::hyperstr::{GRAY, GREEN, LIGHT_RED, RESET};
+
+/// The prefix of environment variables that override keys, e.g. `LIGHTFETCH_ART__MODE=kitty`.
+pub const ENV_PREFIX: &str = "LIGHTFETCH_";
+
+impl Helio {
+    /// Override keys with `LIGHTFETCH_<SECTION>__<KEY>` environment variables.
+    /// Spaces and the `^` of sub keys become underscores, `[GENERAL] ^ case prefix` is `LIGHTFETCH_GENERAL__CASE_PREFIX`.
+    /// Unknown keys only print a warning, other programs could use the same prefix.
+    /// These overrides are not written by `save`.
+    ///
+    /// # Arguments:
+    /// * `vars`: The environment variables, usually `std::env::vars()`.
+    ///
+    /// # Returns:
+    /// A `LightError` if a value is invalid.
+    ///
+    pub fn override_env<I: IntoIterator<Item = (String, String)>>(
+        &mut self,
+        vars: I,
+    ) -> Result<(), LightError> {
+        // They only last for this run, `save` doesn't write them.
+        let edits = self.edits.len();
+        for (name, value) in vars {
+            let (section, key) = match name
+                .strip_prefix(ENV_PREFIX)
+                .and_then(|name| name.split_once("__"))
+            {
+                Some(parts) => parts,
+                None => continue,
+            };
+            match self.override_key(section, key, &value) {
+                Err(e) if matches!(e.level, ErrorLevel::Warning) => eprint!("{}", e),
+                result => result?,
+            }
+        }
+        self.edits.truncate(edits);
+        Ok(())
+    }
+
+    /// Override a key with `SECTION.key=value`, like `--set "GENERAL.auto center=false"`.
+    /// The key can also be written like in environment variables, e.g. `GENERAL.auto_center=false`.
+    ///
+    /// # Arguments:
+    /// * `text`: The override.
+    ///
+    /// # Returns:
+    /// A `LightError` if the override is malformed, the key is unknown or the value is invalid.
+    ///
+    pub fn override_str(&mut self, text: &str) -> Result<(), LightError> {
+        let (path, value) = text.split_once('=').unwrap_or((text, ""));
+        match path.split_once('.') {
+            Some((section, key)) if !value.is_empty() => self
+                .override_key(section.trim(), key.trim(), value.trim())
+                .map_err(|e| LightError::new(e.message, ErrorLevel::Config)),
+            _ => Err(LightError::new(
+                format!(
+                    "Override {LIGHT_RED}'{}'{RESET} invalid {GRAY}| Use 'SECTION.key=value', e.g. 'ART.mode=ascii'{RESET}",
+                    text
+                ),
+                ErrorLevel::Config,
+            )),
+        }
+    }
+
+    /// Set a known key to a value that was checked against the schema.
+    fn override_key(&mut self, section: &str, key: &str, value: &str) -> Result<(), LightError> {
+        let schema = match resolve(section, key) {
+            Some(schema) => schema,
+            None => {
+                let keys = SCHEMA.iter().map(|k| k.key);
+                return Err(LightError::new(
+                    format!(
+                        "Can't override {LIGHT_RED}'{}.{}'{RESET}, the key is unknown{}",
+                        section,
+                        key,
+                        schema::suggest(&key.replace('_', " "), keys)
+                    ),
+                    ErrorLevel::Warning,
+                ));
+            }
+        };
+        if let Some(problem) = schema::check(schema.kind, &value.replace('"', "")) {
+            return Err(LightError::new(
+                format!(
+                    "Override of {GREEN}[{}] {}{RESET}: {}",
+                    schema.section, schema.key, problem
+                ),
+                ErrorLevel::Config,
+            ));
+        }
+        self.set_str(schema.section, schema.key, Some(value));
+        Ok(())
+    }
+}
+
+/// Find the key an override refers to, ignoring the case, spaces, underscores and the `^` of sub keys.
+pub(super) fn resolve(section: &str, key: &str) -> Option<&'static Key> {
+    let (section, key) = (normalize(section), normalize(key));
+    SCHEMA
+        .iter()
+        .find(|k| normalize(k.section) == section && normalize(k.key) == key)
+}
+
+/// Turn a name into lowercase words split by underscores, `^ case prefix` becomes `case_prefix`.
+pub(super) fn normalize(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_unique() {
+        for (i, a) in SCHEMA.iter().enumerate() {
+            for b in &SCHEMA[i + 1..] {
+                assert!(
+                    a.section != b.section || normalize(a.key) != normalize(b.key),
+                    "'{}' and '{}' can't be told apart",
+                    a.key,
+                    b.key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_override_env() {
+        let mut config = Helio::new();
+        config
+            .override_env([
+                ("LIGHTFETCH_ART__MODE".to_string(), "kitty".to_string()),
+                (
+                    "LIGHTFETCH_GENERAL__CASE_PREFIX".to_string(),
+                    "<".to_string(),
+                ),
+                ("LIGHTFETCH_GENERAL__NOPE".to_string(), "1".to_string()),
+                ("LIGHTFETCH_CONFIG".to_string(), "a.ini".to_string()),
+                ("HOME".to_string(), "/home".to_string()),
+            ])
+            .unwrap();
+        assert_eq!(config.get_str("ART", "mode").unwrap(), "kitty");
+        assert_eq!(config.get_str("GENERAL", "^ case prefix").unwrap(), "<");
+
+        let error = config
+            .override_env([("LIGHTFETCH_FETCH__REVERSE".to_string(), "yes".to_string())])
+            .unwrap_err();
+        assert!(matches!(error.level, ErrorLevel::Config));
+    }
+
+    #[test]
+    fn test_override_str() {
+        let mut config = Helio::new();
+        config.override_str("FETCH.reverse=true").unwrap();
+        config.override_str("general.auto center = false").unwrap();
+        assert!(config.get_bool("FETCH", "reverse").unwrap());
+        assert!(!config.get_bool("GENERAL", "auto center").unwrap());
+
+        assert!(config.override_str("FETCH.reverse").is_err());
+        assert!(config.override_str("reverse=true").is_err());
+        assert!(config.override_str("FETCH.reverse=maybe").is_err());
+        assert!(config.override_str("FETCH.nope=1").is_err());
+    }
+}
@@ -0,0 +1,511 @@
+use super::include::INCLUDE;
+use crate::error::ErrorLevel;
+use crate::util::color::Color;
+use crate::util::hyperstr::{GRAY, GREEN, LIGHT_RED, RESET};
+use std::path::PathBuf;
+
+/// What kind of value a key takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Bool,
+    /// A whole number between `min` and `max`.
+    Int {
+        min: u32,
+        max: u32,
+    },
+    /// A number that can have decimal places, not below 0.
+    Float,
+    Text,
+    /// A color, see `Color::parse`.
+    Color,
+    /// One of the listed values, ignoring the case.
+    Choice(&'static [&'static str]),
+}
+
+/// A key every config has.
+pub struct Key {
+    pub section: &'static str,
+    pub key: &'static str,
+    pub kind: Kind,
+    pub default: &'static str,
+}
+
+const fn key(section: &'static str, key: &'static str, kind: Kind, default: &'static str) -> Key {
+    Key {
+        section,
+        key,
+        kind,
+        default,
+    }
+}
+
+const BOOL: Kind = Kind::Bool;
+const TEXT: Kind = Kind::Text;
+const COLOR: Kind = Kind::Color;
+const ANY: Kind = Kind::Int {
+    min: 0,
+    max: u32::MAX,
+};
+
+pub const FILTERS: [&str; 5] = ["Nearest", "Gaussian", "Triangle", "Catmull", "Lanczos"];
+pub const ART_MODES: [&str; 7] = [
+    "ascii",
+    "auto",
+    "image",
+    "kitty",
+    "iterm",
+    "sixel",
+    "characters",
+];
+pub const COLOR_DEPTHS: [&str; 5] = ["truecolor", "256", "16", "none", "mono"];
+pub const DITHERS: [&str; 3] = ["none", "floyd-steinberg", "ordered"];
+pub const UNITS: [&str; 2] = ["binary", "decimal"];
+/// Filesystems that only live in memory or belong to the kernel.
+/// Network filesystems too, asking an unreachable server for the usage can hang.
+const HIDDEN_FILESYSTEMS: &str =
+    "tmpfs, devtmpfs, ramfs, overlay, squashfs, proc, sysfs, cgroup, cgroup2, \
+devpts, mqueue, hugetlbfs, debugfs, tracefs, securityfs, pstore, bpf, configfs, fusectl, \
+autofs, binfmt_misc, efivarfs, nsfs, \
+nfs, nfs4, cifs, smb3, smbfs, 9p, afs, ceph, glusterfs, davfs, fuse.sshfs, fuse.rclone";
+
+/// Every known key, in the order of the default config.
+pub const SCHEMA: [Key; 54] = [
+    key("GENERAL", "auto center", BOOL, "true"),
+    key("GENERAL", "enable variables", BOOL, "true"),
+    key("GENERAL", "^ prefix", TEXT, "{"),
+    key("GENERAL", "^ suffix", TEXT, "}"),
+    key("GENERAL", "^ case sensitive", BOOL, "false"),
+    key("GENERAL", "enable case variables", BOOL, "true"),
+    key("GENERAL", "^ uppercase letter", TEXT, "U"),
+    key("GENERAL", "^ lowercase letter", TEXT, "L"),
+    key("GENERAL", "^ case prefix", TEXT, "[{letter}]"),
+    key("GENERAL", "^ case suffix", TEXT, "[/{letter}]"),
+    key("FETCH", "text", TEXT, ""),
+    key("FETCH", "gap", TEXT, "   "),
+    key("FETCH", "reverse", BOOL, "false"),
+    key("FETCH", "left padding", Kind::Int { min: 0, max: 200 }, "0"),
+    key(
+        "FETCH",
+        "right padding",
+        Kind::Int { min: 0, max: 200 },
+        "0",
+    ),
+    key("ART", "mode", Kind::Choice(&ART_MODES), "image"),
+    key("ART", "^ query terminal", BOOL, "true"),
+    key(
+        "ART",
+        "^ query timeout",
+        Kind::Int { min: 0, max: 10000 },
+        "100",
+    ),
+    key(
+        "ART",
+        "path",
+        TEXT,
+        "~/.config/lightfetch/images/default.png",
+    ),
+    key("IMAGE", "filter", Kind::Choice(&FILTERS), "Gaussian"),
+    key("IMAGE", "size", Kind::Int { min: 1, max: 500 }, "15"),
+    key(
+        "IMAGE",
+        "color depth",
+        Kind::Choice(&COLOR_DEPTHS),
+        "truecolor",
+    ),
+    key(
+        "IMAGE",
+        "^ dither",
+        Kind::Choice(&DITHERS),
+        "floyd-steinberg",
+    ),
+    key("IMAGE", "characters", TEXT, " .:-=+*%@"),
+    key("IMAGE", "^ edges", BOOL, "false"),
+    key("IMAGE", "^ tint", Kind::Choice(&COLOR_DEPTHS), "none"),
+    key(
+        "CPU",
+        "sample interval",
+        Kind::Int { min: 0, max: 5000 },
+        "200",
+    ),
+    key("CPU", "color", COLOR, "blue"),
+    key("MEMORY", "rounding", Kind::Int { min: 0, max: 10 }, "2"),
+    key("MEMORY", "units", Kind::Choice(&UNITS), "decimal"),
+    key("MEMORY", "kb", TEXT, " KB"),
+    key("MEMORY", "mb", TEXT, " MB"),
+    key("MEMORY", "gb", TEXT, " GB"),
+    key("MEMORY", "tb", TEXT, " TB"),
+    key("MEMORY", "color", COLOR, "blue"),
+    key("UPTIME", "suffix", BOOL, "true"),
+    key("UPTIME", "^ plurals", BOOL, "true"),
+    key("UPTIME", "^ character", TEXT, "s"),
+    key("UPTIME", "hide if zero", BOOL, "true"),
+    key("UPTIME", "day", TEXT, " day"),
+    key("UPTIME", "hour", TEXT, " hour"),
+    key("UPTIME", "minute", TEXT, " minute"),
+    key("UPTIME", "second", TEXT, " second"),
+    key("UPTIME", "color", COLOR, "blue"),
+    key("DISK", "show", TEXT, "/"),
+    key("DISK", "hide types", TEXT, HIDDEN_FILESYSTEMS),
+    key("DISK", "color", COLOR, "blue"),
+    key("CACHE", "enable", BOOL, "true"),
+    key("CACHE", "^ images", BOOL, "true"),
+    key("CACHE", "^ variables", BOOL, "true"),
+    key("CACHE", "clear it?", BOOL, "true"),
+    key("CACHE", "^ delete oldest if more than", ANY, "20"),
+    key(
+        "CACHE",
+        "recache variables if older than x hours",
+        Kind::Float,
+        "24",
+    ),
+    key("CACHE", "^ path", TEXT, "~/.config/lightfetch/cache"),
+];
+
+/// Sections without any keys, they are still known.
+const EMPTY_SECTIONS: [&str; 1] = ["ADVANCED MODE"];
+
+/// A problem with the config, at a position in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// The line, starting at 1.
+    pub line: usize,
+    /// The column, starting at 1.
+    pub column: usize,
+    /// The file, if it's known.
+    pub file: Option<PathBuf>,
+    pub message: String,
+    /// Unknown sections and keys are a `Warning`, they are ignored. Bad values are a `Config` error.
+    pub level: ErrorLevel,
+}
+
+/// Find the schema of a key.
+pub fn find(section: &str, key: &str) -> Option<&'static Key> {
+    SCHEMA.iter().find(|k| k.section == section && k.key == key)
+}
+
+/// Check a config against the schema.
+/// Reports unknown sections and keys, values of the wrong type and values out of range.
+///
+/// # Arguments:
+/// * `input`: The contents of the config.
+///
+/// # Returns:
+/// Every issue, in the order of the file.
+///
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut section: Option<String> = None;
+
+    for (num, raw_line) in input.lines().enumerate() {
+        // Comments and indented lines work the same as in `Helio::parse`.
+        let line = super::strip_comment(raw_line);
+        let trimmed = line.trim();
+        if trimmed.is_empty() || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let column = |part: &str| {
+            raw_line[..part.as_ptr() as usize - raw_line.as_ptr() as usize]
+                .chars()
+                .count()
+                + 1
+        };
+
+        if let (Some(0), Some(end)) = (trimmed.find('['), trimmed.rfind(']')) {
+            let name = trimmed[1..end].trim();
+            section = Some(name.to_string());
+            if let Some(message) = check_section(name) {
+                issues.push(Issue {
+                    file: None,
+                    line: num + 1,
+                    column: column(trimmed) + 1,
+                    message,
+                    level: ErrorLevel::Warning,
+                });
+            }
+            continue;
+        }
+
+        let section = match section.as_deref() {
+            Some(section) => section,
+            None => continue,
+        };
+        let (key, value) = match trimmed.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (trimmed, ""),
+        };
+        match check_key(section, key, &value.replace('"', "")) {
+            Some(Problem::Key(message)) => issues.push(Issue {
+                file: None,
+                line: num + 1,
+                column: column(trimmed),
+                message,
+                level: ErrorLevel::Warning,
+            }),
+            Some(Problem::Value(message)) => issues.push(Issue {
+                file: None,
+                line: num + 1,
+                column: if value.is_empty() {
+                    column(trimmed) + trimmed.chars().count()
+                } else {
+                    column(value)
+                },
+                message,
+                level: ErrorLevel::Config,
+            }),
+            None => {}
+        }
+    }
+    issues
+}
+
+/// What's wrong with a key, found by `check_key`.
+pub(crate) enum Problem {
+    /// The key itself is unknown.
+    Key(String),
+    /// The value doesn't fit the key.
+    Value(String),
+}
+
+/// Every known section.
+pub(crate) fn sections() -> impl Iterator<Item = &'static str> {
+    SCHEMA.iter().map(|k| k.section).chain(EMPTY_SECTIONS)
+}
+
+/// Check a section name against the schema.
+///
+/// # Returns:
+/// The message for an unknown section, `None` if it's known.
+///
+pub(crate) fn check_section(name: &str) -> Option<String> {
+    if sections().any(|s| s == name) {
+        return None;
+    }
+    Some(format!(
+        "Unknown section {LIGHT_RED}'{}'{RESET}{}",
+        name,
+        suggest(name, sections())
+    ))
+}
+
+/// Check a key and its value against the schema.
+/// Keys of unknown sections are not checked, the section is already reported.
+///
+/// # Arguments:
+/// * `section`: The section of the key.
+/// * `key`: The key.
+/// * `value`: The value, without quotes.
+///
+/// # Returns:
+/// What's wrong, `None` if the key is fine.
+///
+pub(crate) fn check_key(section: &str, key: &str, value: &str) -> Option<Problem> {
+    // Includes can be anywhere, the included file is validated on its own.
+    if key == INCLUDE || !sections().any(|s| s == section) {
+        return None;
+    }
+    let schema = match find(section, key) {
+        Some(schema) => schema,
+        None => {
+            let keys = SCHEMA
+                .iter()
+                .filter(|k| k.section == section)
+                .map(|k| k.key);
+            return Some(Problem::Key(format!(
+                "Unknown key {LIGHT_RED}'{}'{RESET} in {GRAY}[{}]{RESET}{}",
+                key,
+                section,
+                suggest(key, keys)
+            )));
+        }
+    };
+    check(schema.kind, value).map(|problem| {
+        Problem::Value(format!(
+            "{GRAY}[{}] {}{RESET}: {}",
+            schema.section, schema.key, problem
+        ))
+    })
+}
+
+/// Check a value against its kind.
+///
+/// # Returns:
+/// What's wrong with the value, `None` if it's fine.
+///
+pub(crate) fn check(kind: Kind, value: &str) -> Option<String> {
+    match kind {
+        Kind::Bool => value.parse::<bool>().err().map(|_| {
+            format!("{LIGHT_RED}'{value}'{RESET} is not a boolean {GRAY}| Use 'true' or 'false'{RESET}")
+        }),
+        Kind::Int { min, max } => match value.parse::<u32>() {
+            Err(_) => Some(format!(
+                "{LIGHT_RED}'{value}'{RESET} is not a whole number {GRAY}| Use a number from {min} to {max}{RESET}"
+            )),
+            Ok(n) if n < min || n > max => Some(format!(
+                "{LIGHT_RED}'{value}'{RESET} is out of range {GRAY}| Use a number from {min} to {max}{RESET}"
+            )),
+            Ok(_) => None,
+        },
+        Kind::Float => match value.parse::<f64>() {
+            Ok(n) if n.is_finite() && n >= 0.0 => None,
+            _ => Some(format!(
+                "{LIGHT_RED}'{value}'{RESET} is not a number {GRAY}| Use a number like '1.5'{RESET}"
+            )),
+        },
+        Kind::Text => None,
+        Kind::Color => Color::parse(value).is_none().then(|| {
+            format!("{LIGHT_RED}'{value}'{RESET} is not a color {GRAY}| Use a name like 'light_red', '#rrggbb', 'rgb(r, g, b)' or a number from 0 to 255{RESET}")
+        }),
+        Kind::Choice(choices) => {
+            if choices.iter().any(|c| c.eq_ignore_ascii_case(value)) {
+                return None;
+            }
+            Some(format!(
+                "{LIGHT_RED}'{value}'{RESET} is invalid{} {GRAY}| Available: {}{RESET}",
+                suggest(value, choices.iter().copied()),
+                choices
+                    .iter()
+                    .map(|c| format!("'{}'", c))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+    }
+}
+
+/// Create a "did you mean" hint for the closest of the candidates.
+///
+/// # Returns:
+/// The hint, empty if nothing is close enough.
+///
+pub(crate) fn suggest<'a>(input: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    let input = input.to_lowercase();
+    candidates
+        .map(|c| (distance(&input, &c.to_lowercase()), c))
+        .filter(|(d, c)| *d <= (c.chars().count() / 3).max(2))
+        .min_by_key(|(d, _)| *d)
+        .map_or(String::new(), |(_, c)| {
+            format!(", did you mean {GREEN}'{}'{RESET}?", c)
+        })
+}
+
+/// The Levenshtein distance between two strings.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::helio::{default::DEFAULT, Helio};
+    use crate::util::hyperstr::Ansi;
+
+    fn messages(input: &str) -> Vec<(usize, usize, String)> {
+        validate(input)
+            .into_iter()
+            .map(|i| (i.line, i.column, i.message.strip_ansi_colors()))
+            .collect()
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(validate(DEFAULT), Vec::new());
+
+        // The schema matches the default config.
+        let map = Helio::new().parse(DEFAULT.to_string()).unwrap();
+        let count: usize = map.values().map(|keys| keys.len()).sum();
+        assert_eq!(count, SCHEMA.len());
+        for key in SCHEMA.iter().filter(|k| k.key != "text") {
+            let value = map[key.section][key.key].clone().unwrap();
+            assert_eq!(value.replace('"', ""), key.default, "{}", key.key);
+        }
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(
+            messages("[ CACHE ]\nenabel = true\n[ IMAEG ]\nsize = 1"),
+            vec![
+                (
+                    2,
+                    1,
+                    "Unknown key 'enabel' in [CACHE], did you mean 'enable'?".to_string()
+                ),
+                (
+                    3,
+                    2,
+                    "Unknown section 'IMAEG', did you mean 'IMAGE'?".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            messages("[ART]\nwhatever = 1")[0].2,
+            "Unknown key 'whatever' in [ART]"
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(
+            messages("[IMAGE]\nsize = 0\nfilter = Gausian\n  ^ edges = yes\n^ edges = yes # no"),
+            vec![
+                (
+                    2,
+                    8,
+                    "[IMAGE] size: '0' is out of range | Use a number from 1 to 500".to_string()
+                ),
+                (
+                    3,
+                    10,
+                    "[IMAGE] filter: 'Gausian' is invalid, did you mean 'Gaussian'? | Available: 'Nearest', 'Gaussian', 'Triangle', 'Catmull', 'Lanczos'".to_string()
+                ),
+                (
+                    5,
+                    11,
+                    "[IMAGE] ^ edges: 'yes' is not a boolean | Use 'true' or 'false'".to_string()
+                ),
+            ]
+        );
+        assert!(validate("[IMAGE]\nfilter = gaussian\ncolor depth = \"256\"").is_empty());
+        assert_eq!(
+            messages("[CPU]\ncolor = pink\n[CACHE]\nrecache variables if older than x hours = -1"),
+            vec![
+                (
+                    2,
+                    9,
+                    "[CPU] color: 'pink' is not a color | Use a name like 'light_red', '#rrggbb', 'rgb(r, g, b)' or a number from 0 to 255".to_string()
+                ),
+                (
+                    4,
+                    43,
+                    "[CACHE] recache variables if older than x hours: '-1' is not a number | Use a number like '1.5'".to_string()
+                ),
+            ]
+        );
+        assert!(validate(
+            "[DISK]\ncolor = \"#ff8800\"\n[CACHE]\nrecache variables if older than x hours = 0.5"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("same", "same"), 0);
+    }
+}
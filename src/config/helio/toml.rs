@@ -0,0 +1,736 @@
+use super::overrides::{normalize, resolve};
+use super::schema::{self, Issue, Problem};
+use super::strip_comment;
+use crate::error::{ErrorLevel, LightError};
+use crate::util::hyperstr::{GRAY, LIGHT_RED, RESET};
+use std::path::Path;
+
+/// A part of a TOML config that ends up in the config map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Item {
+    /// A table header, e.g. `[GENERAL]`.
+    Section {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+    Key {
+        section: String,
+        key: String,
+        /// The value as text, arrays and multi-line strings are joined with `\n`.
+        value: String,
+        line: usize,
+        column: usize,
+        /// Where each line of the value starts in the file.
+        lines: Vec<(usize, usize)>,
+    },
+}
+
+/// Check if a config is written in TOML, by its file extension.
+pub(crate) fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("toml"))
+}
+
+/// Parse a TOML config into the sections and keys `Helio` uses.
+/// Strings, multi-line strings, numbers, booleans and arrays are supported, arrays become one line per value.
+/// Keys with spaces can be quoted, `"auto center"`, or written with underscores, `auto_center`.
+///
+/// # Arguments:
+/// * `input`: The contents of the config.
+/// * `default`: The section of keys before the first table.
+///
+/// # Returns:
+/// The sections and keys in the order of the file, a `LightError` if it's not valid TOML.
+///
+pub(crate) fn parse(input: &str, default: &str) -> Result<Vec<Item>, LightError> {
+    let mut cursor = Cursor::new(input);
+    let mut items = Vec::new();
+    let mut section = default.to_string();
+
+    loop {
+        cursor.skip_blank();
+        let (line, column) = cursor.at();
+        match cursor.peek() {
+            None => break,
+            Some('[') => {
+                cursor.bump();
+                if cursor.peek() == Some('[') {
+                    return Err(cursor.error("Arrays of tables are not supported"));
+                }
+                cursor.skip_space();
+                section = canonical_section(&cursor.key()?);
+                cursor.skip_space();
+                cursor.expect(']')?;
+                cursor.end_of_line()?;
+                items.push(Item::Section {
+                    name: section.clone(),
+                    line,
+                    column,
+                });
+            }
+            Some(_) => {
+                let key = cursor.key()?;
+                cursor.skip_space();
+                cursor.expect('=')?;
+                cursor.skip_space();
+                let text = cursor.value()?;
+                cursor.end_of_line()?;
+                // TOML keys can't have spaces without quotes, `auto_center` is `auto center`.
+                let (section, key) = match resolve(&section, &key) {
+                    Some(schema) if schema::find(&section, &key).is_none() => {
+                        (schema.section.to_string(), schema.key.to_string())
+                    }
+                    _ => (section.clone(), key),
+                };
+                items.push(Item::Key {
+                    section,
+                    key,
+                    value: text.value,
+                    line,
+                    column,
+                    lines: text.lines,
+                });
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Check the items of a TOML config against the schema, like `schema::validate` does for INI.
+pub(crate) fn validate(items: &[Item]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for item in items {
+        match item {
+            Item::Section { name, line, column } => {
+                if let Some(message) = schema::check_section(name) {
+                    issues.push(Issue {
+                        file: None,
+                        line: *line,
+                        column: *column + 1,
+                        message,
+                        level: ErrorLevel::Warning,
+                    });
+                }
+            }
+            Item::Key {
+                section,
+                key,
+                value,
+                line,
+                column,
+                lines,
+            } => {
+                let (at, message, level) = match schema::check_key(section, key, value) {
+                    Some(Problem::Key(message)) => ((*line, *column), message, ErrorLevel::Warning),
+                    Some(Problem::Value(message)) => (lines[0], message, ErrorLevel::Config),
+                    None => continue,
+                };
+                issues.push(Issue {
+                    file: None,
+                    line: at.0,
+                    column: at.1,
+                    message,
+                    level,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Convert an INI config to TOML, keeping the comments and the layout.
+/// Used to create the default config when the path ends with `.toml`.
+///
+/// # Arguments:
+/// * `input`: The contents of the INI config.
+///
+/// # Returns:
+/// The TOML config.
+///
+pub(crate) fn from_ini(input: &str) -> String {
+    let lines: Vec<&str> = input.lines().collect();
+    let continues = |line: &str| {
+        let content = strip_comment(line);
+        !content.trim().is_empty() && content.starts_with(char::is_whitespace)
+    };
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let content = strip_comment(line);
+        let trimmed = content.trim();
+        i += 1;
+        if trimmed.is_empty() {
+            out.push(line.to_string());
+            continue;
+        }
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            let name = trimmed[1..trimmed.len() - 1].trim();
+            out.push(line.replacen(name, &toml_key(name), 1));
+            continue;
+        }
+
+        let comment = &line[content.trim_end().len()..];
+        let (key, value) = trimmed
+            .split_once('=')
+            .map_or((trimmed, ""), |(k, v)| (k.trim(), v.trim()));
+        let mut values = vec![value.replace('"', "")];
+        while i < lines.len() && continues(lines[i]) {
+            values.push(strip_comment(lines[i]).trim().to_string());
+            i += 1;
+        }
+
+        let value = match values.as_slice() {
+            [value] if value.parse::<bool>().is_ok() || value.parse::<u32>().is_ok() => {
+                value.clone()
+            }
+            [value] => quote(value),
+            values => format!("'''\n{}'''", values.join("\n")),
+        };
+        out.push(format!("{} = {}{}", toml_key(key), value, comment));
+    }
+    out.join("\n") + "\n"
+}
+
+/// Write a key so TOML reads it back the same, quoted if it isn't a bare key.
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+/// Write a basic string.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Get the name of a known section written another way, `[general]` is `[GENERAL]`.
+fn canonical_section(name: &str) -> String {
+    let normalized = normalize(name);
+    schema::sections()
+        .find(|s| normalize(s) == normalized)
+        .map_or_else(|| name.to_string(), String::from)
+}
+
+/// A value being read, with where each of its lines starts.
+struct Text {
+    value: String,
+    lines: Vec<(usize, usize)>,
+}
+
+impl Text {
+    fn new(at: (usize, usize)) -> Self {
+        Self {
+            value: String::new(),
+            lines: vec![at],
+        }
+    }
+
+    fn newline(&mut self, at: (usize, usize)) {
+        self.value.push('\n');
+        self.lines.push(at);
+    }
+}
+
+/// Reads a TOML config character by character, keeping track of the line and column.
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Cursor {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn at(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.bump();
+        }
+    }
+
+    fn error(&self, message: &str) -> LightError {
+        LightError::new(
+            format!(
+                "{} on line {LIGHT_RED}{}{RESET}, column {LIGHT_RED}{}{RESET}",
+                message, self.line, self.column
+            ),
+            ErrorLevel::Config,
+        )
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), LightError> {
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("Expected {GRAY}'{}'{RESET}", c)));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn skip_space(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while self.peek().is_some_and(|c| c != '\n') {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skip spaces, comments and empty lines.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_space();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n' | '\r') => {
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Only spaces and a comment can follow a key or a table header.
+    fn end_of_line(&mut self) -> Result<(), LightError> {
+        self.skip_space();
+        self.skip_comment();
+        if self.peek() == Some('\r') {
+            self.bump();
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(_) => Err(self.error("Expected the end of the line")),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, LightError> {
+        let at = self.at();
+        let key = match self.peek() {
+            Some('"') => {
+                self.bump();
+                self.basic(Text::new(at))?.value
+            }
+            Some('\'') => {
+                self.bump();
+                self.literal(Text::new(at))?.value
+            }
+            _ => {
+                let mut key = String::new();
+                while let Some(c) = self
+                    .peek()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                {
+                    key.push(c);
+                    self.bump();
+                }
+                if key.is_empty() {
+                    return Err(self.error("Expected a key"));
+                }
+                key
+            }
+        };
+        self.skip_space();
+        if self.peek() == Some('.') {
+            return Err(self.error("Dotted keys are not supported"));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Text, LightError> {
+        for (quotes, multi_line) in [("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)] {
+            if !self.starts_with(quotes) {
+                continue;
+            }
+            self.skip(quotes.len());
+            // A line break right after the opening quotes is not part of the string.
+            if multi_line {
+                if self.starts_with("\r\n") {
+                    self.skip(2);
+                } else if self.peek() == Some('\n') {
+                    self.bump();
+                }
+            }
+            let text = Text::new(self.at());
+            return match (quotes.starts_with('"'), multi_line) {
+                (true, true) => self.multi_line_basic(text),
+                (false, true) => self.multi_line_literal(text),
+                (true, false) => self.basic(text),
+                (false, false) => self.literal(text),
+            };
+        }
+        match self.peek() {
+            Some('[') => self.array(),
+            Some('{') => Err(self.error("Inline tables are not supported")),
+            None | Some('\n' | '\r' | '#') => Err(self.error("Missing value")),
+            Some(_) => self.scalar(),
+        }
+    }
+
+    fn basic(&mut self, mut text: Text) -> Result<Text, LightError> {
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("Unterminated string")),
+                Some('"') => {
+                    self.bump();
+                    return Ok(text);
+                }
+                Some('\\') => {
+                    self.bump();
+                    self.escape(&mut text, false)?;
+                }
+                Some(c) => {
+                    text.value.push(c);
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn multi_line_basic(&mut self, mut text: Text) -> Result<Text, LightError> {
+        loop {
+            if self.starts_with("\"\"\"") {
+                // Up to two quotes right before the closing ones are part of the string.
+                while self.starts_with("\"\"\"\"") {
+                    text.value.push('"');
+                    self.bump();
+                }
+                self.skip(3);
+                return Ok(text);
+            }
+            match self.peek() {
+                None => return Err(self.error("Unterminated string")),
+                Some('\\') => {
+                    self.bump();
+                    self.escape(&mut text, true)?;
+                }
+                Some('\r') if self.starts_with("\r\n") => {
+                    self.bump();
+                }
+                Some('\n') => {
+                    self.bump();
+                    text.newline(self.at());
+                }
+                Some(c) => {
+                    text.value.push(c);
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn literal(&mut self, mut text: Text) -> Result<Text, LightError> {
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("Unterminated string")),
+                Some('\'') => return Ok(text),
+                Some(c) => text.value.push(c),
+            }
+        }
+    }
+
+    fn multi_line_literal(&mut self, mut text: Text) -> Result<Text, LightError> {
+        loop {
+            if self.starts_with("'''") {
+                while self.starts_with("''''") {
+                    text.value.push('\'');
+                    self.bump();
+                }
+                self.skip(3);
+                return Ok(text);
+            }
+            match self.peek() {
+                None => return Err(self.error("Unterminated string")),
+                Some('\r') if self.starts_with("\r\n") => {
+                    self.bump();
+                }
+                Some('\n') => {
+                    self.bump();
+                    text.newline(self.at());
+                }
+                Some(c) => {
+                    text.value.push(c);
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// Read an escape sequence, the `\` is already read.
+    fn escape(&mut self, text: &mut Text, multi_line: bool) -> Result<(), LightError> {
+        let c = match self.bump() {
+            Some(c) => c,
+            None => return Err(self.error("Unterminated string")),
+        };
+        let escaped = match c {
+            'b' => '\u{8}',
+            't' => '\t',
+            'n' => {
+                text.newline(self.at());
+                return Ok(());
+            }
+            'f' => '\u{c}',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            'u' | 'U' => {
+                let length = if c == 'u' { 4 } else { 8 };
+                let hex: String = (0..length).filter_map(|_| self.bump()).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => return Err(self.error("Invalid unicode escape")),
+                }
+            }
+            // A backslash at the end of a line joins it with the next one.
+            ' ' | '\t' | '\r' | '\n' if multi_line => {
+                self.skip_space();
+                if c != '\n' && !matches!(self.peek(), Some('\n' | '\r')) {
+                    return Err(self.error("Invalid escape"));
+                }
+                while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+                    self.bump();
+                }
+                return Ok(());
+            }
+            _ => return Err(self.error("Invalid escape")),
+        };
+        text.value.push(escaped);
+        Ok(())
+    }
+
+    /// Read an array, every value becomes a line.
+    fn array(&mut self) -> Result<Text, LightError> {
+        self.bump();
+        let mut text = Text {
+            value: String::new(),
+            lines: Vec::new(),
+        };
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.bump();
+                break;
+            }
+            let item = self.value()?;
+            if !text.lines.is_empty() {
+                text.value.push('\n');
+            }
+            text.value.push_str(&item.value);
+            text.lines.extend(item.lines);
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => {
+                    self.bump();
+                    break;
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+        if text.lines.is_empty() {
+            text.lines.push(self.at());
+        }
+        Ok(text)
+    }
+
+    /// Read a boolean or a number.
+    fn scalar(&mut self) -> Result<Text, LightError> {
+        let mut text = Text::new(self.at());
+        while let Some(c) = self
+            .peek()
+            .filter(|c| !matches!(c, ' ' | '\t' | '\r' | '\n' | ',' | ']' | '#'))
+        {
+            text.value.push(c);
+            self.bump();
+        }
+        let number = text.value.replace('_', "");
+        if text.value == "true" || text.value == "false" {
+            Ok(text)
+        } else if number.parse::<f64>().is_ok() {
+            text.value = number.trim_start_matches('+').to_string();
+            Ok(text)
+        } else {
+            Err(self.error(&format!(
+                "Invalid value {LIGHT_RED}'{}'{RESET}, strings need quotes",
+                text.value
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::helio::default::DEFAULT;
+    use crate::config::helio::Helio;
+
+    fn values(input: &str) -> Vec<(String, String, String)> {
+        parse(input, "default")
+            .unwrap()
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Key {
+                    section,
+                    key,
+                    value,
+                    ..
+                } => Some((section, key, value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = r#"top = 1 # comment
+[ GENERAL ]
+"auto center" = false
+case_prefix = '[{letter}]'
+^ = "x"
+[FETCH]
+text = """
+a\tb \
+   c
+d"""
+gap = ["  ", 'x', 1_000, +2]
+"#;
+        assert!(parse(input, "default").is_err());
+        let input = input.replace("^ = \"x\"\n", "");
+        assert_eq!(
+            values(&input),
+            [
+                ("default", "top", "1"),
+                ("GENERAL", "auto center", "false"),
+                ("GENERAL", "^ case prefix", "[{letter}]"),
+                ("FETCH", "text", "a\tb c\nd"),
+                ("FETCH", "gap", "  \nx\n1000\n2"),
+            ]
+            .map(|(s, k, v)| (s.to_string(), k.to_string(), v.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        let items = parse("[FETCH]\ntext = '''\n  {a}\n{b}'''\n", "default").unwrap();
+        match &items[1] {
+            Item::Key {
+                line,
+                column,
+                lines,
+                ..
+            } => {
+                assert_eq!((*line, *column), (2, 1));
+                assert_eq!(lines, &[(3, 1), (4, 1)]);
+            }
+            item => panic!("{:?}", item),
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        for input in [
+            "[[a]]",
+            "a.b = 1",
+            "a = {}",
+            "a = word",
+            "a = \"open",
+            "a = 1 b",
+            "[a",
+            "a = \"\\q\"",
+        ] {
+            assert!(parse(input, "default").is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let items = parse(
+            "[GENERAL]\nauto_center = \"maybe\"\n[NOPE]\nx = 1\n[FETCH]\nleft_padding = 5\n",
+            "default",
+        )
+        .unwrap();
+        let issues: Vec<(usize, usize)> = validate(&items)
+            .iter()
+            .map(|i| (i.line, i.column))
+            .collect();
+        assert_eq!(issues, [(2, 16), (3, 2)]);
+    }
+
+    #[test]
+    fn test_from_ini() {
+        let toml = from_ini(DEFAULT);
+        let items = parse(&toml, "default").unwrap();
+        assert!(validate(&items).is_empty());
+
+        let mut config = Helio::new();
+        for item in items {
+            if let Item::Key {
+                section,
+                key,
+                value,
+                ..
+            } = item
+            {
+                config.set_str(&section, &key, Some(&value));
+            }
+        }
+        let ini = Helio::new().parse(DEFAULT.to_string()).unwrap();
+        for (section, keys) in ini {
+            for (key, value) in keys {
+                let value = value.unwrap_or_default().replace('"', "");
+                assert_eq!(config.get_str(&section, &key).unwrap(), value, "{}", key);
+            }
+        }
+    }
+}
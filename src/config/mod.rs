@@ -1,4 +1,5 @@
 pub mod cache;
+pub(crate) mod check;
 pub mod helio;
 
 #[cfg(test)]
@@ -18,4 +19,149 @@ pub(crate) mod helio_tests {
         println!("{:?}", vec);
         println!("{}", vec[2]);
     }
+
+    #[test]
+    pub fn load() {
+        use crate::error::ErrorLevel;
+        use std::fs::OpenOptions;
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join("lightfetch_load");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("config.ini");
+        let append = |text: &str| {
+            let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+
+        let mut config = Helio::new();
+        config.load(&path).unwrap();
+        // Unknown settings are only a warning.
+        append("\n[FETCH]\nmystery = 1\n[MYSTERY]\nkey = 1\n");
+        config.load(&path).unwrap();
+
+        append("[FETCH]\nleft padding = nope\n");
+        let error = config.load(&path).unwrap_err();
+        assert_eq!(error.level, ErrorLevel::Config);
+        assert!(error.message.contains("left padding"));
+        assert!(!error.message.contains("mystery"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn include() {
+        let dir = std::env::temp_dir().join("lightfetch_include");
+        std::fs::create_dir_all(dir.join("themes")).unwrap();
+        let base = dir.join("themes/base.ini");
+        let path = dir.join("config.ini");
+        std::fs::write(
+            &base,
+            "[FETCH]\nleft padding = 4\n[GENERAL]\nauto center = false\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &path,
+            "[FETCH]\nleft padding = 1\ninclude = themes/base.ini\n[GENERAL]\nauto center = true\n",
+        )
+        .unwrap();
+
+        let mut config = Helio::new();
+        assert!(config.read(&path).unwrap().is_empty());
+        assert_eq!(config.get_int("FETCH", "left padding").unwrap(), 4);
+        assert!(config.get_bool("GENERAL", "auto center").unwrap());
+        assert_eq!(config.files(), [base.clone(), path.clone()]);
+
+        std::fs::write(&base, "include = ../config.ini\n").unwrap();
+        let error = config.read(&path).unwrap_err();
+        assert!(error.message.contains("includes itself"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn toml() {
+        let dir = std::env::temp_dir().join("lightfetch_toml");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(dir.join("base.ini"), "[ART]\nmode = ascii\n").unwrap();
+        std::fs::write(
+            &path,
+            "include = \"base.ini\"\n[FETCH]\ntext = [\"a\", \"b\"]\nleft_padding = 2\n",
+        )
+        .unwrap();
+
+        let mut config = Helio::new();
+        assert!(config.read(&path).unwrap().is_empty());
+        assert_eq!(config.get_str("ART", "mode").unwrap(), "ascii");
+        assert_eq!(
+            config.get_str("FETCH", "text").unwrap(),
+            format!("a{}b", util::hyperstr::N)
+        );
+        assert_eq!(config.get_int("FETCH", "left padding").unwrap(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn getters() {
+        use crate::image::dither::Dither;
+        use crate::util::color::{Color, ColorDepth};
+        use crate::util::hyperstr::Ansi;
+
+        let mut config = Helio::new();
+        config.set_str("TEST", "float", Some("1.5"));
+        config.set_str("TEST", "list", Some("a, b,\n c,,"));
+        config.set_str("TEST", "color", Some("\"#ff8800\""));
+        config.set_str("TEST", "depth", Some("Mono"));
+        config.set_str("TEST", "dither", Some("floyd"));
+
+        assert_eq!(config.get_float("TEST", "float").unwrap(), 1.5);
+        assert_eq!(config.get_list("TEST", "list").unwrap(), ["a", "b", "c"]);
+        assert_eq!(
+            config.get_color("TEST", "color").unwrap(),
+            Color::Rgb([255, 136, 0])
+        );
+        assert_eq!(
+            config.get_enum::<ColorDepth>("TEST", "depth").unwrap(),
+            ColorDepth::Mono
+        );
+        assert!(config.get_float("TEST", "list").is_err());
+        assert!(config.get_color("TEST", "float").is_err());
+        assert_eq!(
+            config
+                .get_enum::<Dither>("TEST", "dither")
+                .unwrap_err()
+                .message
+                .strip_ansi_colors(),
+            "Section 'TEST': Key 'dither' is not a dithering: 'floyd' | Available: 'none', 'floyd-steinberg', 'ordered'"
+        );
+    }
+
+    #[test]
+    pub fn save() {
+        let dir = std::env::temp_dir().join("lightfetch_save");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.ini");
+        let input = "# Mine.\n[ FETCH ]\nreverse = false # for now\n";
+        std::fs::write(&path, input).unwrap();
+
+        let mut config = Helio::new();
+        config.read(&path).unwrap();
+        config.save(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), input);
+
+        config
+            .override_env([("LIGHTFETCH_ART__MODE".to_string(), "ascii".to_string())])
+            .unwrap();
+        config.override_str("FETCH.reverse=true").unwrap();
+        config.save(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Mine.\n[ FETCH ]\nreverse = true # for now\n"
+        );
+        // The config before the save is kept.
+        assert_eq!(
+            std::fs::read_to_string(dir.join("config.ini.bak")).unwrap(),
+            input
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,13 +1,18 @@
-use crate::fetch::cpu::{cpu, CpuInfo};
+use crate::fetch::cpu::{cpu, get_cpu_freq, get_loadavg, CpuInfo};
+use crate::fetch::disk::{disks, variable_name, DiskFilter, DiskInfo};
 use crate::fetch::distro::{distro, DistroInfo};
+use crate::fetch::gpu::{gpu, GpuInfo};
+use std::collections::HashMap;
 
 use crate::fetch::memory::{memory, MemInfo};
-use crate::fetch::packages::{packages, PackageInfo};
+use crate::fetch::packages::{packages, set_counts, PackageInfo, MANAGERS};
 use crate::fetch::shell::{shell, ShellInfo};
 use crate::fetch::terminal::{terminal, TerminalInfo};
-use crate::fetch::uptime::{uptime, UptimeInfo};
+use crate::fetch::uptime::{uptime, UptimeFormat, UptimeInfo};
 use crate::fetch::user::{user, UserInfo};
+use crate::util::mth::SizeFormat;
 
+#[allow(dead_code)]
 pub struct FetchData {
     pub user: UserInfo<String>,
     pub distro: DistroInfo<String>,
@@ -17,7 +22,15 @@ pub struct FetchData {
     pub uptime: UptimeInfo<String>,
     pub memory: MemInfo<String>,
     pub cpu: CpuInfo<String>,
+    /// The names of all GPUs, split by commas.
     pub gpu: String,
+    pub gpus: Vec<GpuInfo<String>>,
+    pub gpu_count: String,
+    /// The shown mounts with their usage, e.g. `/: 45.20 GB / 100.00 GB (45%)`.
+    pub disk: String,
+    pub disks: Vec<DiskInfo<String>>,
+    /// The placeholders of each mount, they depend on the mount points.
+    pub disk_variables: Vec<(String, String)>,
 }
 
 pub static UNKNOWN: &str = "UNKNOWN";
@@ -50,13 +63,13 @@ impl FetchData {
                 terminal_version: UNKNOWN.to_string(),
             },
             packages: PackageInfo {
-                pacman: UNKNOWN.to_string(),
-                pacman_version: UNKNOWN.to_string(),
-                cargo: UNKNOWN.to_string(),
-                cargo_version: UNKNOWN.to_string(),
+                summary: UNKNOWN.to_string(),
+                total: UNKNOWN.to_string(),
+                counts: vec![UNKNOWN.to_string(); MANAGERS.len()],
             },
             uptime: UptimeInfo {
                 uptime_raw: UNKNOWN.to_string(),
+                pretty: UNKNOWN.to_string(),
                 days: UNKNOWN.to_string(),
                 hours: UNKNOWN.to_string(),
                 minutes: UNKNOWN.to_string(),
@@ -73,14 +86,19 @@ impl FetchData {
                 used: UNKNOWN.to_string(),
                 buffers: UNKNOWN.to_string(),
                 cached: UNKNOWN.to_string(),
+                percent: UNKNOWN.to_string(),
                 swap_total: UNKNOWN.to_string(),
                 swap_free: UNKNOWN.to_string(),
                 swap_used: UNKNOWN.to_string(),
+                swap_percent: UNKNOWN.to_string(),
             },
             cpu: CpuInfo {
                 mhz: UNKNOWN.to_string(),
                 ghz: UNKNOWN.to_string(),
+                max_mhz: UNKNOWN.to_string(),
+                max_ghz: UNKNOWN.to_string(),
                 bogomips: UNKNOWN.to_string(),
+                sockets: UNKNOWN.to_string(),
                 cores: UNKNOWN.to_string(),
                 threads: UNKNOWN.to_string(),
                 vendor: UNKNOWN.to_string(),
@@ -91,17 +109,28 @@ impl FetchData {
                 flags: UNKNOWN.to_string(),
                 cache_size: UNKNOWN.to_string(),
                 cpu_load: UNKNOWN.to_string(),
+                core_loads: UNKNOWN.to_string(),
+                loadavg: [UNKNOWN, UNKNOWN, UNKNOWN].map(String::from),
             },
             gpu: UNKNOWN.to_string(),
+            gpus: Vec::new(),
+            gpu_count: "0".to_string(),
+            disk: UNKNOWN.to_string(),
+            disks: Vec::new(),
+            disk_variables: Vec::new(),
         }
     }
 
     /// Update or get the uptime data.
+    ///
+    /// # Arguments:
+    /// * `format`: How the uptime is formatted.
+    ///
     /// # Returns:
     /// The Uptime Data.
     ///
-    pub fn get_uptime(&mut self) {
-        uptime(&mut self.uptime)
+    pub fn get_uptime(&mut self, format: &UptimeFormat) {
+        uptime(&mut self.uptime, format)
     }
 
     /// Update or get the terminal data.
@@ -146,11 +175,15 @@ impl FetchData {
     }
 
     /// Update or get the memory data.
+    ///
+    /// # Arguments:
+    /// * `format`: How the sizes are formatted.
+    ///
     /// # Returns:
     /// The Memory Data.
     ///
-    pub fn get_memory(&mut self) {
-        memory(&mut self.memory)
+    pub fn get_memory(&mut self, format: &SizeFormat) {
+        memory(&mut self.memory, format)
     }
 
     /// Update or get the cpu data.
@@ -160,4 +193,253 @@ impl FetchData {
     pub fn get_cpu(&mut self) {
         cpu(&mut self.cpu)
     }
+
+    /// Update or get the gpu data.
+    /// # Returns:
+    /// The Gpu Data.
+    ///
+    pub fn get_gpu(&mut self) {
+        self.set_gpus(gpu())
+    }
+
+    /// Set the GPUs and the values that sum them up.
+    fn set_gpus(&mut self, gpus: Vec<GpuInfo<String>>) {
+        self.gpu = if gpus.is_empty() {
+            UNKNOWN.to_string()
+        } else {
+            gpus.iter()
+                .map(|gpu| gpu.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.gpu_count = gpus.len().to_string();
+        self.gpus = gpus;
+    }
+
+    /// Update or get the disk data.
+    ///
+    /// # Arguments:
+    /// * `filter`: Which mounts are shown.
+    /// * `format`: How the sizes are formatted.
+    ///
+    /// # Returns:
+    /// The Disk Data.
+    ///
+    pub fn get_disks(&mut self, filter: &DiskFilter, format: &SizeFormat) {
+        self.set_disks(disks(filter, format))
+    }
+
+    /// Set the disks and their placeholders.
+    fn set_disks(&mut self, disks: Vec<DiskInfo<String>>) {
+        let summary =
+            |disk: &DiskInfo<String>| format!("{} / {} ({}%)", disk.used, disk.total, disk.percent);
+        if !disks.is_empty() {
+            self.disk = disks
+                .iter()
+                .map(|disk| format!("{}: {}", disk.mount, summary(disk)))
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+        self.disk_variables = disks
+            .iter()
+            .flat_map(|disk| {
+                let name = variable_name(&disk.mount);
+                [
+                    (format!("DISK_{}", name), summary(disk)),
+                    (format!("DISK_{}_USED", name), disk.used.clone()),
+                    (format!("DISK_{}_TOTAL", name), disk.total.clone()),
+                    (format!("DISK_{}_PERCENT", name), disk.percent.clone()),
+                    (format!("DISK_{}_FSTYPE", name), disk.fstype.clone()),
+                ]
+            })
+            .collect();
+        self.disks = disks;
+    }
+
+    /// Update only the load averages and frequency, the rest of the cpu data can come from the cache.
+    pub fn get_cpu_live(&mut self) {
+        if let Some(loadavg) = get_loadavg() {
+            self.cpu.loadavg = loadavg;
+        }
+        if let Some((mhz, ghz)) = get_cpu_freq() {
+            self.cpu.mhz = mhz;
+            self.cpu.ghz = ghz;
+        }
+    }
+
+    /// Set how busy the cpu was, see `fetch::cpu::sample`.
+    ///
+    /// # Arguments:
+    /// * `total`: The usage of all cores in percent.
+    /// * `cores`: The usage of each core in percent.
+    ///
+    pub fn set_cpu_usage(&mut self, total: String, cores: Vec<String>) {
+        self.cpu.cpu_load = total;
+        self.cpu.core_loads = cores
+            .iter()
+            .map(|core| format!("{}%", core))
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    /// Get the values that barely change between runs, see `config::cache::variables`.
+    ///
+    /// # Returns:
+    /// The values by their key.
+    ///
+    pub fn cached(&self) -> HashMap<String, String> {
+        [
+            ("distro.name", &self.distro.name),
+            ("distro.pretty_name", &self.distro.pretty_name),
+            ("distro.build", &self.distro.build),
+            ("distro.id", &self.distro.id),
+            ("distro.architecture", &self.distro.architecture),
+            ("distro.kernel", &self.distro.kernel),
+            ("cpu.mhz", &self.cpu.mhz),
+            ("cpu.ghz", &self.cpu.ghz),
+            ("cpu.max_mhz", &self.cpu.max_mhz),
+            ("cpu.max_ghz", &self.cpu.max_ghz),
+            ("cpu.bogomips", &self.cpu.bogomips),
+            ("cpu.sockets", &self.cpu.sockets),
+            ("cpu.cores", &self.cpu.cores),
+            ("cpu.threads", &self.cpu.threads),
+            ("cpu.vendor", &self.cpu.vendor),
+            ("cpu.model", &self.cpu.model),
+            ("cpu.family", &self.cpu.family),
+            ("cpu.stepping", &self.cpu.stepping),
+            ("cpu.model_name", &self.cpu.model_name),
+            ("cpu.flags", &self.cpu.flags),
+            ("cpu.cache_size", &self.cpu.cache_size),
+            ("gpu.count", &self.gpu_count),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .chain(
+            MANAGERS
+                .iter()
+                .zip(&self.packages.counts)
+                .map(|((name, _), count)| (format!("packages.{}", name), count.clone())),
+        )
+        // One line per GPU, the fields split by tabs.
+        .chain(self.gpus.iter().enumerate().map(|(i, gpu)| {
+            (
+                format!("gpu.{}", i + 1),
+                format!("{}\t{}\t{}", gpu.name, gpu.driver, gpu.vram),
+            )
+        }))
+        .collect()
+    }
+
+    /// Fill in the values of `cached` from the cache.
+    ///
+    /// # Arguments:
+    /// * `values`: The cached values by their key.
+    ///
+    /// # Returns:
+    /// False if a value is missing, nothing gets changed then.
+    ///
+    pub fn restore(&mut self, values: &HashMap<String, String>) -> bool {
+        if !self.cached().keys().all(|key| values.contains_key(key)) {
+            return false;
+        }
+        let value = |key: &str| values[key].clone();
+        self.distro.name = value("distro.name");
+        self.distro.pretty_name = value("distro.pretty_name");
+        self.distro.build = value("distro.build");
+        self.distro.id = value("distro.id");
+        self.distro.architecture = value("distro.architecture");
+        self.distro.kernel = value("distro.kernel");
+        let counts = MANAGERS
+            .iter()
+            .map(|(name, _)| value(&format!("packages.{}", name)))
+            .collect();
+        set_counts(&mut self.packages, counts);
+        self.cpu.mhz = value("cpu.mhz");
+        self.cpu.ghz = value("cpu.ghz");
+        self.cpu.max_mhz = value("cpu.max_mhz");
+        self.cpu.max_ghz = value("cpu.max_ghz");
+        self.cpu.bogomips = value("cpu.bogomips");
+        self.cpu.sockets = value("cpu.sockets");
+        self.cpu.cores = value("cpu.cores");
+        self.cpu.threads = value("cpu.threads");
+        self.cpu.vendor = value("cpu.vendor");
+        self.cpu.model = value("cpu.model");
+        self.cpu.family = value("cpu.family");
+        self.cpu.stepping = value("cpu.stepping");
+        self.cpu.model_name = value("cpu.model_name");
+        self.cpu.flags = value("cpu.flags");
+        self.cpu.cache_size = value("cpu.cache_size");
+        let count: usize = value("gpu.count").parse().unwrap_or_default();
+        let gpus = (1..=count)
+            .filter_map(|i| {
+                let gpu = values.get(&format!("gpu.{}", i))?;
+                let mut fields = gpu.split('\t').map(String::from);
+                Some(GpuInfo {
+                    name: fields.next()?,
+                    driver: fields.next().unwrap_or_default(),
+                    vram: fields.next().unwrap_or_default(),
+                })
+            })
+            .collect();
+        self.set_gpus(gpus);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore() {
+        let mut data = FetchData::new();
+        data.distro.name = "Arch Linux".to_string();
+        data.cpu.model_name = "Ryzen".to_string();
+        let mut counts = vec!["0".to_string(); MANAGERS.len()];
+        counts[0] = "1234".to_string();
+        set_counts(&mut data.packages, counts);
+        data.set_gpus(vec![GpuInfo {
+            name: "AMD/ATI Radeon RX 6800".to_string(),
+            driver: "amdgpu".to_string(),
+            vram: String::new(),
+        }]);
+        let values = data.cached();
+
+        let mut restored = FetchData::new();
+        assert!(restored.restore(&values));
+        assert_eq!(restored.distro.name, "Arch Linux");
+        assert_eq!(restored.cpu.model_name, "Ryzen");
+        assert_eq!(restored.packages.summary, "1234 (pacman)");
+        assert_eq!(restored.gpus, data.gpus);
+        assert_eq!(restored.gpu, "AMD/ATI Radeon RX 6800");
+        assert_eq!(restored.gpu_count, "1");
+
+        let mut partial = values.clone();
+        partial.remove("gpu.count");
+        let mut restored = FetchData::new();
+        assert!(!restored.restore(&partial));
+        assert_eq!(restored.distro.name, UNKNOWN);
+    }
+
+    #[test]
+    fn test_set_disks() {
+        let disk = |mount: &str, percent: &str| DiskInfo {
+            mount: mount.to_string(),
+            fstype: "ext4".to_string(),
+            used: "45.00 GB".to_string(),
+            total: "100.00 GB".to_string(),
+            percent: percent.to_string(),
+        };
+        let mut data = FetchData::new();
+        data.set_disks(vec![disk("/", "45"), disk("/home", "12")]);
+        assert_eq!(
+            data.disk,
+            "/: 45.00 GB / 100.00 GB (45%), /home: 45.00 GB / 100.00 GB (12%)"
+        );
+        let variables: HashMap<String, String> = data.disk_variables.into_iter().collect();
+        assert_eq!(variables["DISK_ROOT"], "45.00 GB / 100.00 GB (45%)");
+        assert_eq!(variables["DISK_/HOME_PERCENT"], "12");
+        assert_eq!(variables["DISK_/HOME_FSTYPE"], "ext4");
+        assert_eq!(variables.len(), 10);
+    }
 }
@@ -17,6 +17,8 @@ fn err(s: &str, c: &str, text: &str) -> std::fmt::Result {
     exit(1);
 }
 
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorLevel {
     Config,
     Warning,
@@ -1,10 +1,21 @@
 use crate::util;
-use crate::util::mth::{floor, mhz_to_ghz};
+use crate::util::mth::{mhz_to_ghz, percent};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 
+#[allow(dead_code)]
 pub struct CpuInfo<String> {
+    /// The current frequency of the fastest core.
     pub mhz: String,
     pub ghz: String,
+    /// The highest frequency the cores can reach.
+    pub max_mhz: String,
+    pub max_ghz: String,
     pub bogomips: String,
+    pub sockets: String,
     pub cores: String,
     pub threads: String,
     pub vendor: String,
@@ -14,51 +25,486 @@ pub struct CpuInfo<String> {
     pub model_name: String,
     pub flags: String,
     pub cache_size: String,
+    /// How busy all cores were while sampling, in percent.
     pub cpu_load: String,
+    /// How busy each core was, e.g. `12% 3% 40% 7%`.
+    pub core_loads: String,
+    /// The load averages of the last 1, 5 and 15 minutes.
+    pub loadavg: [String; 3],
 }
 
+/// The vendors of ARM cores by their `CPU implementer`.
+const ARM_VENDORS: [(u32, &str); 12] = [
+    (0x41, "ARM"),
+    (0x42, "Broadcom"),
+    (0x43, "Cavium"),
+    (0x46, "Fujitsu"),
+    (0x48, "HiSilicon"),
+    (0x4e, "NVIDIA"),
+    (0x50, "APM"),
+    (0x51, "Qualcomm"),
+    (0x53, "Samsung"),
+    (0x61, "Apple"),
+    (0x6d, "Microsoft"),
+    (0xc0, "Ampere"),
+];
+
+/// The names of ARM cores by their `CPU implementer` and `CPU part`.
+const ARM_PARTS: [(u32, u32, &str); 40] = [
+    (0x41, 0xb76, "ARM1176"),
+    (0x41, 0xc07, "Cortex-A7"),
+    (0x41, 0xc08, "Cortex-A8"),
+    (0x41, 0xc09, "Cortex-A9"),
+    (0x41, 0xc0f, "Cortex-A15"),
+    (0x41, 0xd01, "Cortex-A32"),
+    (0x41, 0xd02, "Cortex-A34"),
+    (0x41, 0xd03, "Cortex-A53"),
+    (0x41, 0xd04, "Cortex-A35"),
+    (0x41, 0xd05, "Cortex-A55"),
+    (0x41, 0xd06, "Cortex-A65"),
+    (0x41, 0xd07, "Cortex-A57"),
+    (0x41, 0xd08, "Cortex-A72"),
+    (0x41, 0xd09, "Cortex-A73"),
+    (0x41, 0xd0a, "Cortex-A75"),
+    (0x41, 0xd0b, "Cortex-A76"),
+    (0x41, 0xd0c, "Neoverse-N1"),
+    (0x41, 0xd0d, "Cortex-A77"),
+    (0x41, 0xd40, "Neoverse-V1"),
+    (0x41, 0xd41, "Cortex-A78"),
+    (0x41, 0xd44, "Cortex-X1"),
+    (0x41, 0xd46, "Cortex-A510"),
+    (0x41, 0xd47, "Cortex-A710"),
+    (0x41, 0xd48, "Cortex-X2"),
+    (0x41, 0xd49, "Neoverse-N2"),
+    (0x41, 0xd4d, "Cortex-A715"),
+    (0x41, 0xd4e, "Cortex-X3"),
+    (0x41, 0xd4f, "Neoverse-V2"),
+    (0x41, 0xd80, "Cortex-A520"),
+    (0x41, 0xd81, "Cortex-A720"),
+    (0x41, 0xd82, "Cortex-X4"),
+    (0x51, 0x800, "Kryo 2XX Gold"),
+    (0x51, 0x801, "Kryo 2XX Silver"),
+    (0x51, 0x802, "Kryo 3XX Gold"),
+    (0x51, 0x803, "Kryo 3XX Silver"),
+    (0x51, 0x804, "Kryo 4XX Gold"),
+    (0x51, 0x805, "Kryo 4XX Silver"),
+    (0x61, 0x022, "Icestorm"),
+    (0x61, 0x023, "Firestorm"),
+    (0xc0, 0xac3, "Ampere-1"),
+];
+
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn cpu(mut data: &mut CpuInfo<String>) {
-    for line in util::data::get_data("/proc/cpuinfo", 1000).unwrap().lines() {
-        if line.starts_with("cpu MHz") {
-            let mut j = line.split(':');
-            j.next();
-            data.mhz = j.next().unwrap().trim().to_string();
-            data.ghz = mhz_to_ghz(data.mhz.clone());
-        }
-        if line.starts_with("model name") {
-            data.model_name = line.split(':').nth(1).unwrap().trim().to_string();
-        }
-        if line.starts_with("vendor_id") {
-            let vendor = line.split(':').nth(1).unwrap().trim();
-            match vendor {
-                "GenuineIntel" => data.vendor = "Intel".to_string(),
-                "AuthenticAMD" => data.vendor = "AMD".to_string(),
-                _ => data.vendor = vendor.to_string(),
-            }
-        }
+pub fn cpu(data: &mut CpuInfo<String>) {
+    let cpuinfo = util::data::get_data("/proc/cpuinfo", 4096).unwrap_or_default();
+    parse(&cpuinfo, Path::new("/sys/devices/system/cpu"), data);
+    if let Some(loadavg) = get_loadavg() {
+        data.loadavg = loadavg;
+    }
+}
 
-        if line.starts_with("cpu cores") {
-            data.cores = line.split(':').nth(1).unwrap().trim().to_string();
-        }
+/// Fill in the cpu data.
+///
+/// # Arguments:
+/// * `cpuinfo`: The contents of `/proc/cpuinfo`.
+/// * `sys`: The sysfs directory of the cpus, usually `/sys/devices/system/cpu`.
+/// * `data`: The cpu data to fill in.
+///
+fn parse(cpuinfo: &str, sys: &Path, data: &mut CpuInfo<String>) {
+    // One block per processor, ARM adds one for the whole board at the end.
+    let blocks: Vec<HashMap<&str, &str>> = cpuinfo
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .collect()
+        })
+        .filter(|block: &HashMap<&str, &str>| !block.is_empty())
+        .collect();
+    let processors: Vec<&HashMap<&str, &str>> = blocks
+        .iter()
+        .filter(|block| block.contains_key("processor"))
+        .collect();
+    // The first processor that has it, or the block of the board.
+    let first = |keys: &[&str]| {
+        processors
+            .iter()
+            .copied()
+            .chain(blocks.iter())
+            .find_map(|block| keys.iter().find_map(|key| block.get(key)))
+            .map(|value| value.to_string())
+    };
 
-        if line.starts_with("bogomips") {
-            data.bogomips = line.split(':').nth(1).unwrap().trim().to_string();
+    if let Some(vendor) = first(&["vendor_id"]) {
+        data.vendor = match vendor.as_str() {
+            "GenuineIntel" => "Intel".to_string(),
+            "AuthenticAMD" => "AMD".to_string(),
+            _ => vendor,
+        };
+    }
+    if let Some(name) = first(&["model name", "Processor", "cpu model", "cpu"]) {
+        data.model_name = name;
+    }
+    if let Some(name) = arm_name(&processors) {
+        data.model_name = name;
+    }
+    if let Some(implementer) = first(&["CPU implementer"]) {
+        if let Some((_, vendor)) = ARM_VENDORS
+            .iter()
+            .find(|(id, _)| Some(*id) == hex(&implementer))
+        {
+            data.vendor = vendor.to_string();
+        }
+    }
+    let fields = [
+        (&mut data.family, &["cpu family", "CPU architecture"][..]),
+        (&mut data.model, &["model", "CPU variant"]),
+        (&mut data.stepping, &["stepping", "CPU revision"]),
+        (&mut data.flags, &["flags", "Features"]),
+        (&mut data.cache_size, &["cache size"]),
+        (&mut data.bogomips, &["bogomips", "BogoMIPS"]),
+    ];
+    for (field, keys) in fields {
+        if let Some(value) = first(keys) {
+            *field = value;
         }
+    }
+
+    // Threads are the logical cpus, the threads of a core share its id.
+    let topology: Vec<(String, String)> = cpu_dirs(sys)
+        .into_iter()
+        .map(|cpu| cpu.join("topology"))
+        .filter(|dir| dir.is_dir())
+        .map(|dir| {
+            let read = |file: &str| {
+                fs::read_to_string(dir.join(file))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default()
+            };
+            (read("physical_package_id"), read("core_id"))
+        })
+        .collect();
+    let topology = if topology.is_empty() {
+        processors
+            .iter()
+            .map(|block| {
+                let get = |key: &str| block.get(key).unwrap_or(&"").to_string();
+                let core = block
+                    .get("core id")
+                    .unwrap_or(block.get("processor").unwrap_or(&""));
+                (get("physical id"), core.to_string())
+            })
+            .collect()
+    } else {
+        topology
+    };
+    if !topology.is_empty() {
+        let sockets: BTreeSet<&String> = topology.iter().map(|(socket, _)| socket).collect();
+        let cores: BTreeSet<&(String, String)> = topology.iter().collect();
+        data.sockets = sockets.len().to_string();
+        data.cores = cores.len().to_string();
+        data.threads = topology.len().to_string();
+    }
 
-        if line.starts_with("cpu family") {
-            data.family = line.split(':').nth(1).unwrap().trim().to_string();
+    let max = frequencies(sys, "cpuinfo_max_freq");
+    if let Some(max) = max {
+        data.max_mhz = max.to_string();
+        data.max_ghz = mhz_to_ghz(data.max_mhz.clone());
+    }
+    let current = frequencies(sys, "scaling_cur_freq").or_else(|| {
+        processors
+            .iter()
+            .filter_map(|block| block.get("cpu MHz")?.parse::<f64>().ok())
+            .map(|mhz| mhz as u64)
+            .max()
+    });
+    if let Some(current) = current {
+        data.mhz = current.to_string();
+        data.ghz = mhz_to_ghz(data.mhz.clone());
+        // Without cpufreq the current frequency is all there is.
+        if max.is_none() {
+            data.max_mhz = data.mhz.clone();
+            data.max_ghz = data.ghz.clone();
         }
+    }
+}
 
-        if line.starts_with("cpu threads") {
-            data.threads = line.split(':').nth(1).unwrap().trim().to_string();
+/// Name ARM cores with the `ARM_PARTS` table, e.g. `4x Cortex-A55, 4x Cortex-A76` if they differ.
+fn arm_name(processors: &[&HashMap<&str, &str>]) -> Option<String> {
+    let mut parts: Vec<(&str, usize)> = Vec::new();
+    for block in processors {
+        let implementer = hex(block.get("CPU implementer")?)?;
+        let part = hex(block.get("CPU part")?)?;
+        let name = ARM_PARTS
+            .iter()
+            .find(|(i, p, _)| *i == implementer && *p == part)
+            .map(|(_, _, name)| *name)?;
+        match parts.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => *count += 1,
+            None => parts.push((name, 1)),
         }
     }
-    data.cpu_load = get_cpu_load()
+    match parts.as_slice() {
+        [] => None,
+        [(name, _)] => Some(name.to_string()),
+        _ => Some(
+            parts
+                .iter()
+                .map(|(name, count)| format!("{}x {}", count, name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    }
+}
+
+/// Parse a hexadecimal number like `0x41`.
+fn hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+/// Get the highest frequency of all cpus from a cpufreq file.
+///
+/// # Arguments:
+/// * `sys`: The sysfs directory of the cpus.
+/// * `file`: The file in the `cpufreq` directory of each cpu, in kHz.
+///
+/// # Returns:
+/// The frequency in MHz, `None` without cpufreq.
+///
+fn frequencies(sys: &Path, file: &str) -> Option<u64> {
+    cpu_dirs(sys)
+        .into_iter()
+        .filter_map(|dir| fs::read_to_string(dir.join("cpufreq").join(file)).ok())
+        .filter_map(|khz| khz.trim().parse::<u64>().ok())
+        .max()
+        .map(|khz| khz / 1000)
+}
+
+/// List the `cpuN` directories, offline cpus can leave gaps in the numbers.
+///
+/// # Arguments:
+/// * `sys`: The sysfs directory of the cpus, usually `/sys/devices/system/cpu`.
+///
+/// # Returns:
+/// The directories, sorted by their number.
+///
+fn cpu_dirs(sys: &Path) -> Vec<PathBuf> {
+    let mut cpus: Vec<(u32, PathBuf)> = fs::read_dir(sys)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    let entry = entry.ok()?;
+                    let name = entry.file_name();
+                    let number = name.to_str()?.strip_prefix("cpu")?;
+                    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                        return None;
+                    }
+                    Some((number.parse().ok()?, entry.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    cpus.sort_unstable_by_key(|(number, _)| *number);
+    cpus.into_iter().map(|(_, dir)| dir).collect()
 }
 
-pub fn get_cpu_load() -> String {
-    let s = util::data::get_data("/proc/loadavg", 4096).unwrap();
-    let a: f32 = s.split_whitespace().next().unwrap().parse().unwrap();
-    floor(a * 100.0, 100_f32).to_string()
+/// Get the current frequency, it changes all the time so it can't be cached.
+///
+/// # Returns:
+/// The frequency in MHz and GHz, `None` without cpufreq.
+///
+pub fn get_cpu_freq() -> Option<(String, String)> {
+    let mhz = frequencies(Path::new("/sys/devices/system/cpu"), "scaling_cur_freq")?;
+    Some((mhz.to_string(), mhz_to_ghz(mhz.to_string())))
+}
+
+/// Get the load averages, they change all the time so they can't be cached.
+///
+/// # Returns:
+/// The load averages of the last 1, 5 and 15 minutes.
+///
+pub fn get_loadavg() -> Option<[String; 3]> {
+    let loadavg = util::data::get_data("/proc/loadavg", 64).ok()?;
+    let mut values = loadavg.split_whitespace().map(String::from);
+    Some([values.next()?, values.next()?, values.next()?])
+}
+
+/// Measure how busy the cpu is, by reading `/proc/stat` before and after waiting.
+///
+/// # Arguments:
+/// * `interval`: How long to wait between both reads.
+///
+/// # Returns:
+/// The usage of all cores and of each core in percent, `None` if `/proc/stat` can't be read.
+///
+pub fn sample(interval: Duration) -> Option<(String, Vec<String>)> {
+    let read = || {
+        util::data::get_data("/proc/stat", 4096)
+            .ok()
+            .map(|stat| times(&stat))
+    };
+    let before = read()?;
+    thread::sleep(interval);
+    let after = read()?;
+    let mut usage = before.iter().zip(&after).map(|(before, after)| {
+        let idle = after.0.saturating_sub(before.0) as f64;
+        let total = after.1.saturating_sub(before.1) as f64;
+        percent(total - idle, total)
+    });
+    Some((usage.next()?, usage.collect()))
+}
+
+/// Parse the idle and total time of all cores and of each core from `/proc/stat`.
+fn times(stat: &str) -> Vec<(u64, u64)> {
+    stat.lines()
+        .filter(|line| line.starts_with("cpu"))
+        .map(|line| {
+            // user, nice, system, idle, iowait, irq, softirq and steal, the guest times are part of user.
+            let values: Vec<u64> = line
+                .split_whitespace()
+                .skip(1)
+                .take(8)
+                .filter_map(|value| value.parse().ok())
+                .collect();
+            let idle = values.iter().skip(3).take(2).sum();
+            (idle, values.iter().sum())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::FetchData;
+    use std::env;
+
+    const X86: &str = "processor\t: 0
+vendor_id\t: AuthenticAMD
+cpu family\t: 25
+model\t\t: 33
+model name\t: AMD Ryzen 7 5800X 8-Core Processor
+stepping\t: 0
+cpu MHz\t\t: 2200.000
+cache size\t: 512 KB
+physical id\t: 0
+core id\t\t: 0
+flags\t\t: fpu vme
+bogomips\t: 7600.00
+
+processor\t: 1
+vendor_id\t: AuthenticAMD
+model name\t: AMD Ryzen 7 5800X 8-Core Processor
+cpu MHz\t\t: 4700.000
+physical id\t: 0
+core id\t\t: 0
+
+processor\t: 2
+vendor_id\t: AuthenticAMD
+model name\t: AMD Ryzen 7 5800X 8-Core Processor
+cpu MHz\t\t: 3000.000
+physical id\t: 0
+core id\t\t: 1
+";
+
+    const ARM: &str = "processor\t: 0
+BogoMIPS\t: 108.00
+Features\t: fp asimd evtstrm crc32 cpuid
+CPU implementer\t: 0x41
+CPU architecture: 8
+CPU variant\t: 0x0
+CPU part\t: 0xd03
+CPU revision\t: 4
+
+processor\t: 1
+CPU implementer\t: 0x41
+CPU part\t: 0xd03
+
+processor\t: 2
+CPU implementer\t: 0x41
+CPU part\t: 0xd0b
+
+Hardware\t: BCM2835
+Model\t\t: Raspberry Pi 4 Model B Rev 1.1
+";
+
+    #[test]
+    fn test_x86() {
+        let mut data = FetchData::new().cpu;
+        parse(X86, Path::new("/nonexistent"), &mut data);
+        assert_eq!(data.vendor, "AMD");
+        assert_eq!(data.model_name, "AMD Ryzen 7 5800X 8-Core Processor");
+        assert_eq!(
+            [&data.family, &data.model, &data.stepping],
+            ["25", "33", "0"]
+        );
+        assert_eq!(data.cache_size, "512 KB");
+        assert_eq!(data.flags, "fpu vme");
+        assert_eq!([&data.sockets, &data.cores, &data.threads], ["1", "2", "3"]);
+        assert_eq!([&data.mhz, &data.ghz], ["4700", "4.70"]);
+        assert_eq!(data.max_ghz, "4.70");
+    }
+
+    #[test]
+    fn test_arm() {
+        let mut data = FetchData::new().cpu;
+        parse(ARM, Path::new("/nonexistent"), &mut data);
+        assert_eq!(data.vendor, "ARM");
+        assert_eq!(data.model_name, "2x Cortex-A53, 1x Cortex-A76");
+        assert_eq!(data.flags, "fp asimd evtstrm crc32 cpuid");
+        assert_eq!(data.stepping, "4");
+        assert_eq!(data.threads, "3");
+
+        let single = ARM.replace("0xd0b", "0xd03");
+        parse(&single, Path::new("/nonexistent"), &mut data);
+        assert_eq!(data.model_name, "Cortex-A53");
+    }
+
+    #[test]
+    fn test_times() {
+        let before = times(
+            "cpu  100 0 100 800 0 0 0 0 0 0
+cpu0 50 0 50 400 0 0 0 0 0 0
+cpu1 50 0 50 400 0 0 0 0 0 0
+intr 12345 0 0
+",
+        );
+        assert_eq!(before, [(800, 1000), (400, 500), (400, 500)]);
+        let after = times("cpu  190 0 110 900 0 0 0 0\ncpu0 140 0 60 400 0 0 0 0\n");
+        assert_eq!(after, [(900, 1200), (400, 600)]);
+    }
+
+    #[test]
+    fn test_sysfs() {
+        let sys = env::temp_dir().join("lightfetch_cpu");
+        let _ = fs::remove_dir_all(&sys);
+        // cpu2 is offline, the ones after it still count.
+        for (i, package, core, max, current) in [
+            (0, 0, 0, 4_500_000, 1_000_000),
+            (1, 0, 0, 4_500_000, 3_900_000),
+            (3, 1, 0, 3_000_000, 800_000),
+        ] {
+            let dir = sys.join(format!("cpu{}", i));
+            fs::create_dir_all(dir.join("topology")).unwrap();
+            fs::create_dir_all(dir.join("cpufreq")).unwrap();
+            let write = |file: &str, value: u32| {
+                fs::write(dir.join(file), format!("{}\n", value)).unwrap();
+            };
+            write("topology/physical_package_id", package);
+            write("topology/core_id", core);
+            write("cpufreq/cpuinfo_max_freq", max);
+            write("cpufreq/scaling_cur_freq", current);
+        }
+        // Not a cpu.
+        fs::create_dir_all(sys.join("cpufreq/policy0")).unwrap();
+        fs::create_dir_all(sys.join("cpuidle")).unwrap();
+        assert_eq!(cpu_dirs(&sys).len(), 3);
+
+        let mut data = FetchData::new().cpu;
+        parse(X86, &sys, &mut data);
+        assert_eq!([&data.sockets, &data.cores, &data.threads], ["2", "2", "3"]);
+        assert_eq!([&data.max_mhz, &data.max_ghz], ["4500", "4.50"]);
+        assert_eq!([&data.mhz, &data.ghz], ["3900", "3.90"]);
+        fs::remove_dir_all(&sys).unwrap();
+    }
 }
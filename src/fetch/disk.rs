@@ -0,0 +1,208 @@
+use crate::util::mth::{format_gb_str, percent, SizeFormat};
+use std::ffi::CString;
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskInfo<String> {
+    /// Where the filesystem is mounted, e.g. `/home`.
+    pub mount: String,
+    pub fstype: String,
+    pub used: String,
+    pub total: String,
+    pub percent: String,
+}
+
+/// Which mounts are shown, set in the `[DISK]` section of the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskFilter {
+    /// The mount points to show, every real filesystem if empty.
+    pub show: Vec<String>,
+    /// The filesystem types that aren't disks, e.g. `tmpfs`.
+    pub hide_types: Vec<String>,
+}
+
+/// A line of `/proc/self/mountinfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mount {
+    point: String,
+    fstype: String,
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn disks(filter: &DiskFilter, format: &SizeFormat) -> Vec<DiskInfo<String>> {
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo").unwrap_or_default();
+    select(mounts(&mountinfo), filter)
+        .into_iter()
+        .filter_map(|mount| {
+            let (used, available) = usage(&mount.point)?;
+            // Like df, the blocks reserved for root count as neither.
+            let total = used + available;
+            (total > 0).then(|| DiskInfo {
+                fstype: mount.fstype,
+                used: format_gb_str(used as f64 / 1024.0, format),
+                total: format_gb_str(total as f64 / 1024.0, format),
+                percent: percent(used as f64, total as f64),
+                mount: mount.point,
+            })
+        })
+        .collect()
+}
+
+/// Parse the mounts of `/proc/self/mountinfo`.
+///
+/// # Arguments:
+/// * `mountinfo`: The contents of the file.
+///
+/// # Returns:
+/// The mounts in the order they were mounted, a mount point mounted over keeps its position.
+///
+fn mounts(mountinfo: &str) -> Vec<Mount> {
+    let mut mounts: Vec<Mount> = Vec::new();
+    for line in mountinfo.lines() {
+        // The optional fields end at the ` - ` before the filesystem type.
+        let Some((left, right)) = line.split_once(" - ") else {
+            continue;
+        };
+        let (Some(point), Some(fstype)) = (
+            left.split_whitespace().nth(4),
+            right.split_whitespace().next(),
+        ) else {
+            continue;
+        };
+        let mount = Mount {
+            point: unescape(point),
+            fstype: fstype.to_string(),
+        };
+        match mounts.iter_mut().find(|m| m.point == mount.point) {
+            Some(old) => *old = mount,
+            None => mounts.push(mount),
+        }
+    }
+    mounts
+}
+
+/// Pick the mounts to show, in the order of the config.
+fn select(mounts: Vec<Mount>, filter: &DiskFilter) -> Vec<Mount> {
+    let real: Vec<Mount> = mounts
+        .into_iter()
+        .filter(|mount| !filter.hide_types.contains(&mount.fstype))
+        .collect();
+    if filter.show.is_empty() {
+        return real;
+    }
+    filter
+        .show
+        .iter()
+        .filter_map(|point| real.iter().find(|mount| mount.point == *point).cloned())
+        .collect()
+}
+
+/// Undo the octal escapes of mountinfo, `\040` is a space.
+fn unescape(point: &str) -> String {
+    let mut out = Vec::with_capacity(point.len());
+    let bytes = point.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let code = bytes
+            .get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|code| u8::from_str_radix(std::str::from_utf8(code).ok()?, 8).ok());
+        match code {
+            Some(code) => {
+                out.push(code);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Get the used and available bytes of a filesystem.
+fn usage(point: &str) -> Option<(u64, u64)> {
+    let path = CString::new(point).ok()?;
+    // SAFETY: The path is a valid C string and statvfs only writes into the struct we pass in.
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        stat
+    };
+    let size = stat.f_frsize as u64;
+    let used = (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * size;
+    Some((used, stat.f_bavail as u64 * size))
+}
+
+/// The name of a mount in placeholders, `/` is `ROOT` and others keep their path, e.g. `/HOME`.
+pub fn variable_name(mount: &str) -> String {
+    match mount {
+        "/" => "ROOT".to_string(),
+        _ => mount.to_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid shared:12 - proc proc rw
+24 22 0:5 / /dev rw,nosuid shared:2 - devtmpfs devtmpfs rw,size=8G
+40 22 259:1 / /boot rw,relatime shared:30 - vfat /dev/nvme0n1p1 rw
+41 22 259:3 / /home rw,relatime shared:31 - btrfs /dev/nvme0n1p3 rw,subvol=/home
+42 22 0:40 / /tmp rw shared:32 - tmpfs tmpfs rw
+43 22 8:1 / /mnt/my\\040disk rw - ntfs3 /dev/sda1 rw
+44 22 259:4 / /home rw,relatime shared:33 - xfs /dev/nvme1n1p1 rw
+";
+
+    #[test]
+    fn test_mounts() {
+        let mounts = mounts(MOUNTINFO);
+        let points: Vec<&str> = mounts.iter().map(|m| m.point.as_str()).collect();
+        assert_eq!(
+            points,
+            [
+                "/",
+                "/proc",
+                "/dev",
+                "/boot",
+                "/home",
+                "/tmp",
+                "/mnt/my disk"
+            ]
+        );
+        assert_eq!(mounts[4].fstype, "xfs");
+    }
+
+    #[test]
+    fn test_select() {
+        let mut filter = DiskFilter {
+            show: Vec::new(),
+            hide_types: ["proc", "devtmpfs", "tmpfs"].map(String::from).to_vec(),
+        };
+        let points = |filter: &DiskFilter| {
+            select(mounts(MOUNTINFO), filter)
+                .into_iter()
+                .map(|m| m.point)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(points(&filter), ["/", "/boot", "/home", "/mnt/my disk"]);
+        filter.show = ["/home", "/", "/tmp", "/missing"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(points(&filter), ["/home", "/"]);
+    }
+
+    #[test]
+    fn test_usage() {
+        let (used, available) = usage("/").unwrap();
+        assert!(used + available > 0);
+        assert_eq!(usage("/does/not/exist"), None);
+        assert_eq!(variable_name("/"), "ROOT");
+        assert_eq!(variable_name("/home"), "/HOME");
+    }
+}
@@ -20,7 +20,7 @@ pub struct DistroInfo<String> {
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn distro(mut data: &mut DistroInfo<String>) {
+pub fn distro(data: &mut DistroInfo<String>) {
     for i in util::data::get_data("/etc/os-release", 0)
         .unwrap()
         .split('\n')
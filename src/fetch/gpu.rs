@@ -1,5 +1,235 @@
-#[allow(unused)]
+use crate::util::mth::{format_gb_str, SizeFormat, Units};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuInfo<String> {
+    /// The vendor and the model, e.g. `NVIDIA GeForce RTX 3080`.
+    pub name: String,
+    /// The kernel driver, empty if none is bound.
+    pub driver: String,
+    /// The video memory, empty if the driver doesn't expose it.
+    pub vram: String,
+}
+
+/// Where distributions put the PCI ID database.
+const PCI_IDS: [&str; 3] = [
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+];
+
+/// The vendors of most GPUs, in the format of `pci.ids`, for systems without the database.
+const EMBEDDED_IDS: &str = "\
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+1013  Cirrus Logic
+102b  Matrox Electronics Systems Ltd.
+10de  NVIDIA Corporation
+1234  QEMU
+\t1111  Standard VGA
+13b5  ARM
+15ad  VMware
+\t0405  SVGA II Adapter
+1a03  ASPEED Technology, Inc.
+\t2000  ASPEED Graphics Family
+1af4  Red Hat, Inc.
+\t1050  Virtio 1.0 GPU
+1b36  Red Hat, Inc.
+\t0100  QXL paravirtual graphic card
+1d0f  Amazon.com, Inc.
+5143  Qualcomm Technologies, Inc
+80ee  VirtualBox
+\tbeef  Graphics Adapter
+8086  Intel Corporation
+";
+
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn gpu() -> String {
-    String::new()
+pub fn gpu() -> Vec<GpuInfo<String>> {
+    let ids = PCI_IDS
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .unwrap_or_else(|| EMBEDDED_IDS.to_string());
+    gpus(Path::new("/sys/bus/pci"), &ids)
+}
+
+/// Find the display controllers on the PCI bus.
+///
+/// # Arguments:
+/// * `bus`: The sysfs directory of the bus, usually `/sys/bus/pci`.
+/// * `ids`: The contents of a `pci.ids` file to name the devices with.
+///
+/// # Returns:
+/// The GPUs, ordered by their PCI address.
+///
+fn gpus(bus: &Path, ids: &str) -> Vec<GpuInfo<String>> {
+    let mut devices: Vec<_> = match fs::read_dir(bus.join("devices")) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+        Err(_) => return Vec::new(),
+    };
+    devices.sort_by_key(|entry| entry.file_name());
+
+    let read = |dir: &Path, file: &str| {
+        fs::read_to_string(dir.join(file))
+            .map(|value| value.trim().trim_start_matches("0x").to_lowercase())
+            .ok()
+    };
+    devices
+        .iter()
+        .map(|entry| entry.path())
+        // Class 03 are display controllers: VGA, XGA, 3D and others.
+        .filter(|dir| read(dir, "class").is_some_and(|class| class.starts_with("03")))
+        .filter_map(|dir| {
+            let vendor = read(&dir, "vendor")?;
+            let device = read(&dir, "device")?;
+            let driver = fs::read_link(dir.join("driver"))
+                .ok()
+                .and_then(|link| Some(link.file_name()?.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            // Only amdgpu and radeon expose it.
+            let vram = read(&dir, "mem_info_vram_total")
+                .and_then(|bytes| bytes.parse().ok())
+                .map(vram)
+                .unwrap_or_default();
+            Some(GpuInfo {
+                name: name(ids, &vendor, &device),
+                driver,
+                vram,
+            })
+        })
+        .collect()
+}
+
+/// Name a device with the PCI ID database.
+///
+/// # Arguments:
+/// * `ids`: The contents of a `pci.ids` file.
+/// * `vendor`: The vendor ID, e.g. `10de`.
+/// * `device`: The device ID.
+///
+/// # Returns:
+/// The short name of the vendor and the device, the IDs if they aren't known.
+///
+fn name(ids: &str, vendor: &str, device: &str) -> String {
+    let mut vendor_name = None;
+    let mut device_name = None;
+    for line in ids.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with('\t') {
+            if vendor_name.is_some() {
+                break;
+            }
+            if let Some(name) = line.strip_prefix(vendor) {
+                vendor_name = Some(name.trim());
+            }
+        } else if vendor_name.is_some() && !line.starts_with("\t\t") {
+            if let Some(name) = line[1..].strip_prefix(device) {
+                device_name = Some(name.trim());
+                break;
+            }
+        }
+    }
+
+    let vendor = vendor_name.map_or(format!("Vendor {}", vendor), |name| short(name, true));
+    let device = device_name.map_or(format!("Device {}", device), |name| short(name, false));
+    format!("{} {}", vendor, device)
+}
+
+/// Shorten a name from the database, the part in brackets is the one people know.
+/// `GA102 [GeForce RTX 3080]` becomes `GeForce RTX 3080`, `Intel Corporation` becomes `Intel`.
+fn short(name: &str, vendor: bool) -> String {
+    if let (Some(start), Some(end)) = (name.find('['), name.rfind(']')) {
+        if start < end {
+            return name[start + 1..end].to_string();
+        }
+    }
+    if !vendor {
+        return name.to_string();
+    }
+    let mut name = name;
+    for suffix in [" Corporation", ", Inc.", " Inc.", ", Inc", " Ltd."] {
+        name = name.strip_suffix(suffix).unwrap_or(name);
+    }
+    name.to_string()
+}
+
+/// Format the video memory, e.g. `8 GiB`.
+fn vram(bytes: u64) -> String {
+    let format = SizeFormat {
+        rounding: 0,
+        units: Units::Binary,
+        suffixes: Units::Binary.suffixes().map(String::from),
+    };
+    format_gb_str(bytes as f64 / 1024.0, &format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::os::unix::fs::symlink;
+
+    const IDS: &str = "# pci.ids
+10de  NVIDIA Corporation
+\t1c82  GP107 [GeForce GTX 1050 Ti]
+\t\t1043 8613  Phoenix GeForce GTX 1050 Ti
+\t2206  GA102 [GeForce RTX 3080]
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t73bf  Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]
+8086  Intel Corporation
+\t2206  Wrong vendor
+";
+
+    #[test]
+    fn test_name() {
+        assert_eq!(name(IDS, "10de", "2206"), "NVIDIA GeForce RTX 3080");
+        assert_eq!(
+            name(IDS, "1002", "73bf"),
+            "AMD/ATI Radeon RX 6800/6800 XT / 6900 XT"
+        );
+        assert_eq!(name(IDS, "8086", "46a6"), "Intel Device 46a6");
+        assert_eq!(name(IDS, "abcd", "0001"), "Vendor abcd Device 0001");
+        assert_eq!(name(EMBEDDED_IDS, "1234", "1111"), "QEMU Standard VGA");
+    }
+
+    #[test]
+    fn test_gpus() {
+        let bus = env::temp_dir().join("lightfetch_pci");
+        let _ = fs::remove_dir_all(&bus);
+        let device = |address: &str, class: &str, vendor: &str, id: &str| {
+            let dir = bus.join("devices").join(address);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("class"), format!("{}\n", class)).unwrap();
+            fs::write(dir.join("vendor"), format!("{}\n", vendor)).unwrap();
+            fs::write(dir.join("device"), format!("{}\n", id)).unwrap();
+            dir
+        };
+        let drivers = bus.join("drivers");
+        fs::create_dir_all(drivers.join("amdgpu")).unwrap();
+
+        device("0000:00:1f.3", "0x040300", "0x8086", "0x51c8");
+        let amd = device("0000:03:00.0", "0x030000", "0x1002", "0x73bf");
+        symlink(drivers.join("amdgpu"), amd.join("driver")).unwrap();
+        fs::write(amd.join("mem_info_vram_total"), "17163091968\n").unwrap();
+        device("0000:01:00.0", "0x030200", "0x10de", "0x2206");
+
+        assert_eq!(
+            gpus(&bus, IDS),
+            vec![
+                GpuInfo {
+                    name: "NVIDIA GeForce RTX 3080".to_string(),
+                    driver: String::new(),
+                    vram: String::new(),
+                },
+                GpuInfo {
+                    name: "AMD/ATI Radeon RX 6800/6800 XT / 6900 XT".to_string(),
+                    driver: "amdgpu".to_string(),
+                    vram: "16 GiB".to_string(),
+                },
+            ]
+        );
+        assert_eq!(gpus(&bus.join("missing"), IDS), vec![]);
+        fs::remove_dir_all(&bus).unwrap();
+    }
 }
@@ -1,6 +1,6 @@
 use crate::util;
-use crate::util::mth::format_gb_str;
-use std::ops::Sub;
+use crate::util::mth::{format_gb_str, percent, SizeFormat};
+use std::collections::HashMap;
 
 pub struct MemInfo<String> {
     pub total: String,
@@ -9,92 +9,93 @@ pub struct MemInfo<String> {
     pub used: String,
     pub cached: String,
     pub buffers: String,
+    pub percent: String,
 
     pub swap_used: String,
     pub swap_free: String,
     pub swap_total: String,
+    pub swap_percent: String,
 }
 
-impl MemInfo<String> {
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut String> {
-        let iter = vec![
-            &mut self.total,
-            &mut self.free,
-            &mut self.avail,
-            &mut self.used,
-            &mut self.cached,
-            &mut self.buffers,
-            &mut self.swap_used,
-            &mut self.swap_free,
-            &mut self.swap_total,
-        ];
-        iter.into_iter()
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn memory(data: &mut MemInfo<String>, format: &SizeFormat) {
+    if let Ok(meminfo) = util::data::get_data("/proc/meminfo", 4096) {
+        parse_meminfo(&meminfo, data, format);
     }
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn memory(mut data: &mut MemInfo<String>) {
-    let mut memory_info = MemInfo {
-        total: "".to_string(),
-        free: "".to_string(),
-        avail: "".to_string(),
-        used: "".to_string(),
-        cached: "".to_string(),
-        buffers: "".to_string(),
-        swap_used: "".to_string(),
-        swap_free: "".to_string(),
-        swap_total: "".to_string(),
-    };
-    for line in util::data::get_data("/proc/meminfo", 4096).unwrap().lines() {
-        let mut split = line.split(':');
-        let key = split.next().unwrap().trim().to_string();
-        let value = split
-            .next()
-            .unwrap()
-            .replace("kB", "")
-            .replace(' ', "")
-            .trim()
-            .to_string();
-        let value = value.trim();
-        let value = value.parse::<u64>().unwrap();
-        let value = value / 1024;
-        let value = value.to_string();
-        match key.as_ref() {
-            "MemTotal" => memory_info.total = value,
-            "MemFree" => memory_info.free = value,
-            "MemAvailable" => memory_info.avail = value,
-            "Cached" => memory_info.cached = value,
-            "Buffers" => memory_info.buffers = value,
-            "SwapTotal" => memory_info.swap_total = value,
-            "SwapFree" => memory_info.swap_free = value,
-            _ => (),
-        }
-    }
+/// Fill in the memory data from the contents of `/proc/meminfo`.
+///
+/// # Arguments:
+/// * `input`: The contents of `/proc/meminfo`, the values are in KiB.
+/// * `data`: The memory data to fill in.
+/// * `format`: How the sizes are formatted.
+///
+fn parse_meminfo(input: &str, data: &mut MemInfo<String>, format: &SizeFormat) {
+    let values: HashMap<&str, f64> = input
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim().trim_end_matches("kB").trim().parse().ok()?;
+            Some((key.trim(), value))
+        })
+        .collect();
+    let value = |key: &str| values.get(key).copied().unwrap_or_default();
+
+    let total = value("MemTotal");
+    let free = value("MemFree");
+    // Older kernels don't have MemAvailable, the caches can be freed as well.
+    let avail = values
+        .get("MemAvailable")
+        .copied()
+        .unwrap_or_else(|| free + value("Buffers") + value("Cached"));
     // Do those later, we can calculate them ourselves:
-    memory_info.used = memory_info
-        .total
-        .parse::<f64>()
-        .unwrap()
-        .sub(memory_info.free.parse::<f64>().unwrap())
-        .to_string();
-    memory_info.swap_used = memory_info
-        .swap_total
-        .parse::<u64>()
-        .unwrap()
-        .sub(memory_info.swap_free.parse::<u64>().unwrap())
-        .to_string();
+    let used = (total - avail).max(0.0);
+    let swap_total = value("SwapTotal");
+    let swap_free = value("SwapFree");
+    let swap_used = (swap_total - swap_free).max(0.0);
 
     // Finally apply the formatting:
-    for i in memory_info.iter_mut() {
-        *i = format_gb_str(i.parse::<f64>().unwrap());
+    let size = |value: f64| format_gb_str(value, format);
+    data.total = size(total);
+    data.free = size(free);
+    data.avail = size(avail);
+    data.used = size(used);
+    data.cached = size(value("Cached"));
+    data.buffers = size(value("Buffers"));
+    data.percent = percent(used, total);
+    data.swap_total = size(swap_total);
+    data.swap_free = size(swap_free);
+    data.swap_used = size(swap_used);
+    data.swap_percent = percent(swap_used, swap_total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::FetchData;
+
+    const MEMINFO: &str = "MemTotal:       16000000 kB
+MemFree:         2000000 kB
+MemAvailable:    8000000 kB
+Buffers:          100000 kB
+Cached:          4000000 kB
+SwapTotal:       2000000 kB
+SwapFree:        1500000 kB
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        let mut data = FetchData::new().memory;
+        parse_meminfo(MEMINFO, &mut data, &SizeFormat::default());
+        assert_eq!(data.total, "16.38 GB");
+        assert_eq!(data.used, "8.19 GB");
+        assert_eq!(data.percent, "50");
+        assert_eq!(data.swap_used, "512.00 MB");
+        assert_eq!(data.swap_percent, "25");
+
+        let without_available = MEMINFO.replace("MemAvailable:    8000000 kB\n", "");
+        parse_meminfo(&without_available, &mut data, &SizeFormat::default());
+        assert_eq!(data.avail, "6.25 GB");
     }
-    data.avail = memory_info.avail.clone();
-    data.buffers = memory_info.buffers.clone();
-    data.cached = memory_info.cached.clone();
-    data.free = memory_info.free.clone();
-    data.total = memory_info.total.clone();
-    data.used = memory_info.used.clone();
-    data.swap_free = memory_info.swap_free.clone();
-    data.swap_total = memory_info.swap_total.clone();
-    data.swap_used = memory_info.swap_used.clone();
 }
@@ -1,4 +1,5 @@
 pub mod cpu;
+pub mod disk;
 pub mod distro;
 pub mod gpu;
 pub mod memory;
@@ -1,55 +1,300 @@
 use crate::util;
-use std::fs::read_dir;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 pub struct PackageInfo<String> {
-    pub pacman: String,
-    pub pacman_version: String,
-    pub cargo: String,
-    pub cargo_version: String,
+    /// The managers with packages, e.g. `1234 (pacman), 12 (flatpak)`.
+    pub summary: String,
+    pub total: String,
+    /// The number of packages of each of the `MANAGERS`.
+    pub counts: Vec<String>,
 }
 
+/// The supported package managers, with the placeholder of their count.
+pub const MANAGERS: [(&str, &str); 11] = [
+    ("pacman", "PACKAGES_PACMAN"),
+    ("dpkg", "PACKAGES_DPKG"),
+    ("rpm", "PACKAGES_RPM"),
+    ("apk", "PACKAGES_APK"),
+    ("xbps", "PACKAGES_XBPS"),
+    ("portage", "PACKAGES_PORTAGE"),
+    ("nix", "PACKAGES_NIX"),
+    ("brew", "PACKAGES_BREW"),
+    ("flatpak", "PACKAGES_FLATPAK"),
+    ("snap", "PACKAGES_SNAP"),
+    ("cargo", "PACKAGES_CARGO"),
+];
+
+/// The directories of the rpm database, newer releases moved it to `/usr`.
+const RPM_DBS: [&str; 2] = ["var/lib/rpm", "usr/lib/sysimage/rpm"];
+/// The files of the rpm database: SQLite, Berkeley DB and the NDB of openSUSE.
+const RPM_FILES: [&str; 3] = ["rpmdb.sqlite", "Packages", "Packages.db"];
+/// The prefixes Homebrew installs to on Linux and macOS.
+const BREW_PREFIXES: [&str; 3] = ["home/linuxbrew/.linuxbrew", "opt/homebrew", "usr/local"];
+
 /// Get the total amount of packages installed.
 ///
 ///# Returns:
 /// A `String` containing the total amount of packages installed.
 ///
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn packages(mut data: &mut PackageInfo<String>) {
-    data.cargo = cargo().to_string();
-    data.pacman = pacman().to_string();
+pub fn packages(data: &mut PackageInfo<String>) {
+    let home = util::data::get_env("HOME").unwrap_or_default();
+    let counts = counts(Path::new("/"), Path::new(&home));
+    set_counts(data, counts.iter().map(usize::to_string).collect());
 }
 
-/// Get the total amount of packages installed via pacman.
+/// Set the counts and the values that sum them up.
 ///
-/// # Returns:
-/// A `i32` number of the total amount of packages installed via pacman.
+/// # Arguments:
+/// * `data`: The package data.
+/// * `counts`: The number of packages of each of the `MANAGERS`.
 ///
-pub fn pacman() -> i32 {
-    let mut packages = 0;
-    let pacman_dir = Path::new("/var/lib/pacman/local");
+pub fn set_counts(data: &mut PackageInfo<String>, counts: Vec<String>) {
+    let numbers: Vec<usize> = counts
+        .iter()
+        .map(|count| count.parse().unwrap_or_default())
+        .collect();
+    data.total = numbers.iter().sum::<usize>().to_string();
+    data.summary = summary(&numbers);
+    data.counts = counts;
+}
 
-    if let Ok(read_dir) = read_dir(pacman_dir) {
-        packages = read_dir.count();
+/// List the managers that have packages, e.g. `1234 (pacman), 12 (flatpak)`.
+fn summary(counts: &[usize]) -> String {
+    let parts: Vec<String> = MANAGERS
+        .iter()
+        .zip(counts)
+        .filter(|(_, &count)| count > 0)
+        .map(|((name, _), count)| format!("{} ({})", count, name))
+        .collect();
+    if parts.is_empty() {
+        return "0".to_string();
     }
+    parts.join(", ")
+}
 
-    packages as i32
+/// The files and directories the managers keep their packages in.
+/// The counters read them and the variables cache watches them, so a change to a database invalidates it.
+///
+/// # Arguments:
+/// * `root`: The root of the file system.
+/// * `home`: The home directory of the user.
+///
+/// # Returns:
+/// The paths of each of the `MANAGERS`, most of them don't exist on any one system.
+///
+pub fn databases(root: &Path, home: &Path) -> [Vec<PathBuf>; 11] {
+    let in_dirs = |dirs: &[&str], names: &[&str]| -> Vec<PathBuf> {
+        dirs.iter()
+            .flat_map(|dir| names.iter().map(move |name| root.join(dir).join(name)))
+            .collect()
+    };
+    [
+        vec![root.join("var/lib/pacman/local")],
+        vec![root.join("var/lib/dpkg/status")],
+        in_dirs(&RPM_DBS, &RPM_FILES),
+        vec![root.join("lib/apk/db/installed")],
+        vec![root.join("var/db/xbps")],
+        vec![root.join("var/db/pkg")],
+        vec![
+            home.join(".nix-profile/manifest.json"),
+            root.join("nix/var/nix/profiles/default/manifest.json"),
+        ],
+        in_dirs(&BREW_PREFIXES, &["Cellar", "Caskroom"]),
+        vec![
+            root.join("var/lib/flatpak/app"),
+            home.join(".local/share/flatpak/app"),
+        ],
+        vec![root.join("snap")],
+        vec![
+            home.join(".cargo/.crates2.json"),
+            home.join(".cargo/.crates.toml"),
+        ],
+    ]
 }
 
-/// Get the total amount of packages installed via cargo.
+/// Count the packages of every manager.
+///
+/// # Arguments:
+/// * `root`: The root of the file system.
+/// * `home`: The home directory of the user.
 ///
 /// # Returns:
-/// A `i32` number of the total amount of packages installed via cargo.
-///
-pub fn cargo() -> i32 {
-    let mut packages = 0;
-    let cargo_dir = Path::new("/home")
-        .join(util::data::get_env("HOME").unwrap())
-        .join(".cargo");
-    if cargo_dir.is_dir() {
-        if let Ok(read_dir) = read_dir(cargo_dir) {
-            packages = read_dir.count();
+/// The number of packages of each of the `MANAGERS`, 0 for the ones that aren't installed.
+///
+fn counts(root: &Path, home: &Path) -> Vec<usize> {
+    let read = |path: &Path| fs::read_to_string(path).unwrap_or_default();
+    let [pacman, dpkg, rpm_files, apk, xbps_db, portage, nix, brew, flatpak, snap, cargo_files] =
+        databases(root, home);
+
+    vec![
+        // Every package has a directory, next to a file with the version of the database.
+        dirs(&pacman[0]),
+        read(&dpkg[0])
+            .split("\n\n")
+            .filter(|package| {
+                package
+                    .lines()
+                    .any(|line| line.starts_with("Status:") && line.ends_with(" installed"))
+            })
+            .count(),
+        rpm(root, &rpm_files),
+        read(&apk[0])
+            .lines()
+            .filter(|line| line.starts_with("P:"))
+            .count(),
+        xbps(&xbps_db[0]),
+        // Packages are sorted into categories, e.g. `sys-apps/portage-3.0.30`.
+        fs::read_dir(&portage[0])
+            .map(|categories| {
+                categories
+                    .filter_map(|category| category.ok())
+                    .map(|category| dirs(&category.path()))
+                    .sum()
+            })
+            .unwrap_or_default(),
+        // Each element of a profile has its store paths, the legacy `manifest.nix` isn't counted.
+        nix.iter()
+            .map(|manifest| read(manifest).matches("\"storePaths\"").count())
+            .sum(),
+        brew.iter().map(|dir| dirs(dir)).sum(),
+        // Only apps, the runtimes they need aren't installed on purpose.
+        flatpak.iter().map(|dir| dirs(dir)).sum(),
+        // `/snap/bin` holds the commands of the snaps.
+        dirs(&snap[0]).saturating_sub(snap[0].join("bin").is_dir() as usize),
+        cargo(&cargo_files),
+    ]
+}
+
+/// Count the directories in a directory.
+fn dirs(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .count()
+        })
+        .unwrap_or_default()
+}
+
+/// Count the packages installed via rpm.
+/// The database is SQLite, Berkeley DB or NDB, so rpm itself has to read it.
+///
+/// # Arguments:
+/// * `root`: The root of the file system.
+/// * `files`: The files of the database, see `databases`.
+///
+fn rpm(root: &Path, files: &[PathBuf]) -> usize {
+    if !files.iter().any(|file| file.is_file()) {
+        return 0;
+    }
+    Command::new("rpm")
+        .arg("--root")
+        .arg(root)
+        .args(["-qa", "--nodigest", "--nosignature"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).lines().count())
+        .unwrap_or_default()
+}
+
+/// Count the packages installed via xbps, the package database is a property list.
+fn xbps(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    name.starts_with("pkgdb-") && name.ends_with(".plist")
+                })
+                .map(|entry| {
+                    fs::read_to_string(entry.path())
+                        .unwrap_or_default()
+                        .matches("<string>installed</string>")
+                        .count()
+                })
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+/// Count the packages installed via `cargo install`.
+/// Cargo lists them in `.crates2.json`, older versions only wrote `.crates.toml`.
+fn cargo(files: &[PathBuf]) -> usize {
+    if let Ok(crates) = fs::read_to_string(&files[0]) {
+        return crates.matches("\"bins\":").count();
+    }
+    fs::read_to_string(&files[1])
+        .map(|crates| {
+            crates
+                .lines()
+                .filter(|line| line.starts_with('"') && line.contains(" = ["))
+                .count()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_counts() {
+        let root = env::temp_dir().join("lightfetch_packages");
+        let _ = fs::remove_dir_all(&root);
+        let home = root.join("home/bwte");
+        let write = |path: &str, text: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
         };
+        let dir = |path: &str| fs::create_dir_all(root.join(path)).unwrap();
+
+        dir("var/lib/pacman/local/linux-6.0-1");
+        dir("var/lib/pacman/local/rust-1.64-1");
+        write("var/lib/pacman/local/ALPM_DB_VERSION", "9");
+        write(
+            "var/lib/dpkg/status",
+            "Package: a\nStatus: install ok installed\n\nPackage: b\nStatus: deinstall ok config-files\n",
+        );
+        write("lib/apk/db/installed", "C:x\nP:musl\nV:1\n\nP:busybox\n");
+        write(
+            "var/db/xbps/pkgdb-0.38.plist",
+            "<key>state</key>\n<string>installed</string>\n<key>state</key>\n<string>installed</string>",
+        );
+        dir("var/db/pkg/sys-apps/portage-3.0.30");
+        dir("var/db/pkg/dev-lang/rust-1.64.0");
+        dir("var/db/pkg/dev-lang/python-3.10.8");
+        write(
+            "home/bwte/.nix-profile/manifest.json",
+            r#"{"elements":[{"storePaths":["/nix/store/a"]},{"storePaths":["/nix/store/b"]}],"version":2}"#,
+        );
+        dir("home/linuxbrew/.linuxbrew/Cellar/gcc");
+        dir("opt/homebrew/Caskroom/firefox");
+        dir("var/lib/flatpak/app/org.gimp.GIMP");
+        dir("home/bwte/.local/share/flatpak/app/com.spotify.Client");
+        dir("snap/bin");
+        dir("snap/core");
+        write(
+            "home/bwte/.cargo/.crates2.json",
+            r#"{"installs":{"ripgrep 13.0.0":{"bins":["rg"]},"bat 0.22.1":{"bins":["bat"]}}}"#,
+        );
+
+        let counts = counts(&root, &home);
+        assert_eq!(counts, [2, 1, 0, 2, 2, 3, 2, 2, 2, 1, 2]);
+        assert_eq!(
+            summary(&counts),
+            "2 (pacman), 1 (dpkg), 2 (apk), 2 (xbps), 3 (portage), 2 (nix), 2 (brew), 2 (flatpak), 1 (snap), 2 (cargo)"
+        );
+        assert_eq!(summary(&[0; 11]), "0");
+
+        fs::remove_file(home.join(".cargo/.crates2.json")).unwrap();
+        write("home/bwte/.cargo/.crates.toml", "[v1]\n\"ripgrep 13.0.0 (registry+https://github.com/rust-lang/crates.io-index)\" = [\"rg\"]\n");
+        assert_eq!(cargo(&databases(&root, &home)[10]), 1);
+        fs::remove_dir_all(&root).unwrap();
     }
-    packages as i32
 }
@@ -1,12 +1,13 @@
 use crate::data::UNKNOWN;
 use std::env;
 
+#[allow(dead_code)]
 pub struct ShellInfo<String> {
     pub shell: String,
     pub shell_version: String,
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn shell(mut data: &mut ShellInfo<String>) {
+pub fn shell(data: &mut ShellInfo<String>) {
     data.shell = env::var("SHELL").unwrap_or_else(|_| UNKNOWN.to_string());
 }
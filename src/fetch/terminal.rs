@@ -4,13 +4,14 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::process;
 
+#[allow(dead_code)]
 pub struct TerminalInfo<String> {
     pub terminal: String,
     pub terminal_version: String,
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn terminal(mut data: &mut TerminalInfo<String>, shell: &str) {
+pub fn terminal(data: &mut TerminalInfo<String>, shell: &str) {
     let mut terminal_pid = get_parent(process::id() as i32);
     if let Ok(mut terminal_name) = util::data::get_data(
         PathBuf::from("/proc")
@@ -18,7 +19,7 @@ pub fn terminal(mut data: &mut TerminalInfo<String>, shell: &str) {
             .join("comm"),
         16_385,
     ) {
-        while shell.contains(&terminal_name.replace('\n', "").as_str()) {
+        while shell.contains(terminal_name.replace('\n', "").as_str()) {
             let ppid = get_parent(terminal_pid);
             terminal_pid = ppid;
 
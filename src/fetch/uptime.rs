@@ -2,6 +2,8 @@ use crate::util;
 
 pub struct UptimeInfo<String> {
     pub uptime_raw: String,
+    /// The uptime formatted with the `[UPTIME]` settings, e.g. `2 days 3 hours 4 minutes`.
+    pub pretty: String,
     pub days: String,
     pub hours: String,
     pub minutes: String,
@@ -12,40 +14,141 @@ pub struct UptimeInfo<String> {
     pub z_seconds: String,
 }
 
+/// How the uptime is formatted, set in the `[UPTIME]` section of the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UptimeFormat {
+    /// Add the unit after each value? Otherwise it looks like a clock, e.g. `2:03:04:05`.
+    pub suffix: bool,
+    /// The character added to units of values other than 1, only units that are words get it.
+    pub plural: Option<String>,
+    /// Leave out units with a value of 0.
+    pub hide_zero: bool,
+    /// The units of days, hours, minutes and seconds.
+    pub units: [String; 4],
+}
+
+impl Default for UptimeFormat {
+    fn default() -> Self {
+        UptimeFormat {
+            suffix: true,
+            plural: Some("s".to_string()),
+            hide_zero: true,
+            units: [" day", " hour", " minute", " second"].map(String::from),
+        }
+    }
+}
+
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn uptime(mut data: &mut UptimeInfo<String>) {
+pub fn uptime(data: &mut UptimeInfo<String>, format: &UptimeFormat) {
     let udata = util::data::get_data("/proc/uptime", 50).unwrap();
     let uptime_text = udata.split_whitespace().next().unwrap();
     let uptime_raw = uptime_text.parse::<f64>().unwrap();
-    let days = filter_null((uptime_raw / 86400.0).floor());
-    let hours = filter_null(((uptime_raw / 3600.0) % 24.0).floor());
-    let minutes = filter_null(((uptime_raw / 60.0) % 60.0).floor());
-    let seconds = filter_null((uptime_raw % 60.0).floor());
+    let parts = split(uptime_raw as u64);
     data.uptime_raw = uptime_text.to_string();
-    data.days = days.to_string();
-    data.hours = hours.to_string();
-    data.minutes = minutes.to_string();
-    data.seconds = seconds.to_string();
-    data.z_days = time_prefix(days);
-    data.z_hours = time_prefix(hours);
-    data.z_minutes = time_prefix(minutes);
-    data.z_seconds = time_prefix(seconds);
-}
-
-pub fn filter_null(s: f64) -> String {
-    if s == 0.0 {
-        return "".to_string();
+    data.pretty = humanize(parts, format);
+    data.days = parts[0].to_string();
+    data.hours = parts[1].to_string();
+    data.minutes = parts[2].to_string();
+    data.seconds = parts[3].to_string();
+    data.z_days = time_prefix(parts[0]);
+    data.z_hours = time_prefix(parts[1]);
+    data.z_minutes = time_prefix(parts[2]);
+    data.z_seconds = time_prefix(parts[3]);
+}
+
+/// Split seconds into days, hours, minutes and seconds.
+fn split(seconds: u64) -> [u64; 4] {
+    [
+        seconds / 86400,
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    ]
+}
+
+/// Format the parts of the uptime.
+///
+/// # Arguments:
+/// * `parts`: The days, hours, minutes and seconds.
+/// * `format`: How the uptime is formatted.
+///
+/// # Returns:
+/// The uptime, e.g. `2 days 3 hours` or `2:03:00:00` without suffixes.
+///
+pub fn humanize(parts: [u64; 4], format: &UptimeFormat) -> String {
+    if !format.suffix {
+        // Like a clock, the days only if there are any.
+        let clock = parts[1..]
+            .iter()
+            .map(|&part| time_prefix(part))
+            .collect::<Vec<_>>()
+            .join(":");
+        return match parts[0] {
+            0 if format.hide_zero => clock,
+            days => format!("{}:{}", days, clock),
+        };
+    }
+
+    let mut out: Vec<String> = parts
+        .iter()
+        .zip(&format.units)
+        .filter(|(&part, _)| part != 0 || !format.hide_zero)
+        .map(|(&part, unit)| format!("{}{}{}", part, unit, plural(format, unit, part)))
+        .collect();
+    // A fresh boot still has an uptime.
+    if out.is_empty() {
+        let unit = &format.units[3];
+        out.push(format!("0{}{}", unit, plural(format, unit, 0)));
     }
-    s.to_string()
+    out.join(" ")
 }
 
-pub fn time_prefix(s: String) -> String {
-    let s = s;
-    let mut new: String = String::new();
-    if s.len() == 1 {
-        new.push('0');
-        new.push_str(&s);
-        return new;
+/// Get the plural for a unit, abbreviations like ` d` or ` min.` don't get one.
+fn plural<'a>(format: &'a UptimeFormat, unit: &str, value: u64) -> &'a str {
+    let unit = unit.trim();
+    let word = unit.chars().count() > 1 && unit.ends_with(char::is_alphabetic);
+    match &format.plural {
+        Some(plural) if word && value != 1 => plural,
+        _ => "",
+    }
+}
+
+/// Pad a value with a zero to two digits.
+pub fn time_prefix(value: u64) -> String {
+    format!("{:02}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(2 * 86400 + 3 * 3600 + 4 * 60 + 5), [2, 3, 4, 5]);
+        assert_eq!(split(59), [0, 0, 0, 59]);
+    }
+
+    #[test]
+    fn test_humanize() {
+        let mut format = UptimeFormat::default();
+        assert_eq!(humanize([2, 1, 0, 5], &format), "2 days 1 hour 5 seconds");
+        assert_eq!(humanize([0, 0, 0, 0], &format), "0 seconds");
+
+        // Configs from before the units were words still have the abbreviations.
+        format.units = [" d", " h", " m", " s"].map(String::from);
+        assert_eq!(humanize([2, 3, 4, 0], &format), "2 d 3 h 4 m");
+        assert_eq!(humanize([0, 0, 0, 0], &format), "0 s");
+        format.units[2] = " min".to_string();
+        assert_eq!(humanize([0, 0, 4, 1], &format), "4 mins 1 s");
+
+        format.plural = None;
+        format.hide_zero = false;
+        format.units = [" d", " h", " m", " s"].map(String::from);
+        assert_eq!(humanize([0, 1, 0, 5], &format), "0 d 1 h 0 m 5 s");
+
+        format.suffix = false;
+        assert_eq!(humanize([0, 1, 0, 5], &format), "0:01:00:05");
+        format.hide_zero = true;
+        assert_eq!(humanize([0, 1, 0, 5], &format), "01:00:05");
     }
-    s
 }
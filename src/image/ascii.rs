@@ -0,0 +1,156 @@
+use crate::image::cells;
+use crate::util::color::{luminance, ColorDepth};
+use image::imageops::resize;
+use image::imageops::FilterType;
+use image::io::Reader;
+use image::{ImageError, RgbaImage};
+
+/// Gradients stronger than this are drawn as edges.
+const EDGE_THRESHOLD: f32 = 1.0;
+
+/// Renders images with plain characters, picked by the brightness of the pixels.
+/// Works on every terminal and even in log files.
+pub struct Ascii {
+    pub characters: Vec<char>,
+    pub edges: bool,
+    pub tint: ColorDepth,
+}
+
+impl Ascii {
+    /// Create a new `Ascii` renderer.
+    /// # Parameters:
+    /// - `characters`: The characters from dark to bright
+    /// - `edges`: Draw edges with line characters?
+    /// - `tint`: Color the characters with the color of the pixels
+    pub fn new(characters: &str, edges: bool, tint: ColorDepth) -> Self {
+        let mut characters: Vec<char> = characters.chars().collect();
+        if characters.is_empty() {
+            characters.push('#');
+        }
+        Self {
+            characters,
+            edges,
+            tint,
+        }
+    }
+
+    /// Process an image and convert it to characters.
+    /// # Parameters:
+    /// - `file`: Path to the image
+    /// - `height`: Height of the image in characters
+    /// - `filter`: The filter used for resizing
+    pub fn process(&self, file: &str, height: u32, filter: FilterType) -> String {
+        let img = match Reader::open(file)
+            .map_err(ImageError::from)
+            .and_then(|r| r.decode())
+        {
+            Ok(img) => img.to_rgba8(),
+            Err(e) => {
+                println!("{}", e);
+                return String::new();
+            }
+        };
+        let (cols, rows) = cells(img.width(), img.height(), height);
+        self.render(&resize(&img, cols, rows, filter))
+    }
+
+    /// Convert an image to characters, one character per pixel.
+    /// # Parameters:
+    /// - `img`: The image, already in its final size
+    pub fn render(&self, img: &RgbaImage) -> String {
+        let (w, h) = (img.width(), img.height());
+        // Transparent pixels are treated as dark.
+        let lum: Vec<f32> = img
+            .pixels()
+            .map(|p| luminance([p[0], p[1], p[2]]) * p[3] as f32 / 255.0)
+            .collect();
+        let at = |x: i64, y: i64| -> f32 {
+            let x = x.clamp(0, w as i64 - 1) as u32;
+            let y = y.clamp(0, h as i64 - 1) as u32;
+            lum[(y * w + x) as usize]
+        };
+
+        let mut output = String::new();
+        for y in 0..h {
+            for x in 0..w {
+                let pixel = img.get_pixel(x, y);
+                if pixel[3] == 0 {
+                    output.push(' ');
+                    continue;
+                }
+                let (xi, yi) = (x as i64, y as i64);
+                let mut c = None;
+                if self.edges {
+                    // Sobel operator.
+                    let gx = at(xi + 1, yi - 1) + 2.0 * at(xi + 1, yi) + at(xi + 1, yi + 1)
+                        - at(xi - 1, yi - 1)
+                        - 2.0 * at(xi - 1, yi)
+                        - at(xi - 1, yi + 1);
+                    let gy = at(xi - 1, yi + 1) + 2.0 * at(xi, yi + 1) + at(xi + 1, yi + 1)
+                        - at(xi - 1, yi - 1)
+                        - 2.0 * at(xi, yi - 1)
+                        - at(xi + 1, yi - 1);
+                    if (gx * gx + gy * gy).sqrt() > EDGE_THRESHOLD {
+                        c = Some(edge(gx, gy));
+                    }
+                }
+                let c = c.unwrap_or_else(|| {
+                    let l = at(xi, yi);
+                    let i = (l * (self.characters.len() - 1) as f32).round() as usize;
+                    self.characters[i.min(self.characters.len() - 1)]
+                });
+                output.push_str(&self.tint.fg([pixel[0], pixel[1], pixel[2]]));
+                output.push(c);
+            }
+            if self.tint != ColorDepth::Mono {
+                output.push_str("\x1b[0m");
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Pick a line character along an edge.
+/// The edge runs perpendicular to the gradient, the y axis points down.
+fn edge(gx: f32, gy: f32) -> char {
+    let angle = gy.atan2(gx).to_degrees().rem_euclid(180.0);
+    match angle {
+        a if !(22.5..157.5).contains(&a) => '|',
+        a if a < 67.5 => '/',
+        a if a < 112.5 => '-',
+        _ => '\\',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_render() {
+        let mut img = RgbaImage::new(3, 1);
+        img.put_pixel(0, 0, Rgba([0, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+        img.put_pixel(2, 0, Rgba([255, 255, 255, 0]));
+        let ascii = Ascii::new(" .@", false, ColorDepth::Mono);
+        assert_eq!(ascii.render(&img), " @ \n");
+    }
+
+    #[test]
+    fn test_render_tint() {
+        let mut img = RgbaImage::new(1, 1);
+        img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let ascii = Ascii::new("ab", false, ColorDepth::TrueColor);
+        assert_eq!(ascii.render(&img), "\x1b[38;2;255;0;0ma\x1b[0m\n");
+    }
+
+    #[test]
+    fn test_edges() {
+        assert_eq!(edge(1.0, 0.0), '|');
+        assert_eq!(edge(0.0, 1.0), '-');
+        assert_eq!(edge(1.0, 1.0), '/');
+        assert_eq!(edge(-1.0, 1.0), '\\');
+    }
+}
@@ -1,29 +1,35 @@
 use std::str;
 
-use crate::config::cache::Image;
+use crate::image::dither::{self, Dither};
+use crate::util::color::ColorDepth;
 use image::imageops::resize;
 use image::imageops::FilterType;
 use image::io::Reader;
 use image::ImageError;
 use image::RgbaImage;
 
-pub struct Default {
-    pub image: Image,
-}
+/// The half-block renderer, every character shows two pixels on top of each other.
+pub struct Default;
 
 impl Default {
-    pub fn new(image: Image) -> Self {
-        Self { image }
-    }
-
     /// Process and print an image
     /// # Parameters:
     /// - `file`: Path to the image
     /// - `height`: Height of the image in characters
-    /// - `res`: Are we using the half pixel mode ?
-    pub fn process(file: &str, height: u32, filter: FilterType) -> String {
-        let image: Result<RgbaImage, ImageError> =
-            Ok(Reader::open(file).unwrap().decode().unwrap().to_rgba8());
+    /// - `filter`: The filter used for resizing
+    /// - `depth`: How many colors we are allowed to use
+    /// - `dither`: How the colors are reduced to the color depth
+    pub fn process(
+        file: &str,
+        height: u32,
+        filter: FilterType,
+        depth: ColorDepth,
+        dither: Dither,
+    ) -> String {
+        let image: Result<RgbaImage, ImageError> = Reader::open(file)
+            .map_err(ImageError::from)
+            .and_then(|r| r.decode())
+            .map(|img| img.to_rgba8());
         let img = match image {
             Ok(img) => img,
             Err(e) => {
@@ -33,45 +39,94 @@ impl Default {
         };
         let w = img.width();
         let h = img.height();
-        let img = resize(&img, 2 * w * height / h, height * 2, filter);
+        let mut img = resize(&img, 2 * w * height / h, height * 2, filter);
+        dither::apply(&mut img, depth, dither);
+        Self::render(&img, depth)
+    }
+
+    /// Draw an image with half blocks.
+    /// # Parameters:
+    /// - `img`: The image, already in its final size and colors
+    /// - `depth`: How many colors we are allowed to use
+    pub fn render(img: &RgbaImage, depth: ColorDepth) -> String {
         let mut output = String::new();
-        for i in (0..img.height() - 1).step_by(2) {
+        for i in (0..img.height().saturating_sub(1)).step_by(2) {
             for j in 0..img.width() {
                 let upper = img.get_pixel(j, i); // Upper
                 let lower = img.get_pixel(j, i + 1); // Lower
-                if (*upper)[3] == 0 && (*lower)[3] == 0 {
+                let upper_rgb = [upper[0], upper[1], upper[2]];
+                let lower_rgb = [lower[0], lower[1], lower[2]];
+                if depth == ColorDepth::Mono {
+                    // Without colors only white pixels are drawn.
+                    let upper_on = upper[3] != 0 && depth.nearest(upper_rgb) == [255; 3];
+                    let lower_on = lower[3] != 0 && depth.nearest(lower_rgb) == [255; 3];
+                    output.push(match (upper_on, lower_on) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    });
+                } else if upper[3] == 0 && lower[3] == 0 {
                     // Both transparent
-                    output.push(' ');
-                } else if (*upper)[3] == 0 {
+                    output.push_str("\x1b[0m ");
+                } else if upper[3] == 0 {
                     // Upper transparent
-                    output.push_str(&format!(
-                        "\x1b[48;2;{};{};{}m▀",
-                        (*lower)[0],
-                        (*lower)[1],
-                        (*lower)[2]
-                    ));
-                } else if (*lower)[3] == 0 {
+                    output.push_str("\x1b[0m");
+                    output.push_str(&depth.fg(lower_rgb));
+                    output.push('▄');
+                } else if lower[3] == 0 {
                     // Lower transparent
-                    output.push_str(&format!(
-                        "\x1b[38;2;{};{};{}m▄",
-                        (*upper)[0],
-                        (*upper)[1],
-                        (*upper)[2]
-                    ));
+                    output.push_str("\x1b[0m");
+                    output.push_str(&depth.fg(upper_rgb));
+                    output.push('▀');
                 } else {
-                    output.push_str(&format!(
-                        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀",
-                        (*upper)[0],
-                        (*upper)[1],
-                        (*upper)[2],
-                        (*lower)[0],
-                        (*lower)[1],
-                        (*lower)[2]
-                    ));
+                    output.push_str(&depth.fg(upper_rgb));
+                    output.push_str(&depth.bg(lower_rgb));
+                    output.push('▀');
                 }
             }
-            output.push_str("\x1b[0m\n");
+            if depth != ColorDepth::Mono {
+                output.push_str("\x1b[0m");
+            }
+            output.push('\n');
         }
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn fixture() -> RgbaImage {
+        // Two columns: red over blue, transparent over white.
+        let mut img = RgbaImage::new(2, 2);
+        img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        img.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        img.put_pixel(1, 0, Rgba([0, 0, 0, 0]));
+        img.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        img
+    }
+
+    #[test]
+    fn test_render_truecolor() {
+        assert_eq!(
+            Default::render(&fixture(), ColorDepth::TrueColor),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[0m\x1b[38;2;255;255;255m▄\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_256() {
+        assert_eq!(
+            Default::render(&fixture(), ColorDepth::Ansi256),
+            "\x1b[38;5;196m\x1b[48;5;21m▀\x1b[0m\x1b[38;5;231m▄\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_mono() {
+        assert_eq!(Default::render(&fixture(), ColorDepth::Mono), " ▄\n");
+    }
+}
@@ -0,0 +1,137 @@
+use crate::util::color::ColorDepth;
+use image::RgbaImage;
+
+/// How the colors get reduced to the palette of the color depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Every pixel just takes the closest color.
+    None,
+    /// Spreads the error to the neighbouring pixels, looks the best.
+    FloydSteinberg,
+    /// Uses a fixed pattern, the output stays the same for small changes.
+    Ordered,
+}
+
+/// The 4x4 Bayer matrix for ordered dithering.
+const BAYER: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// Reduce the colors of an image to the palette of the color depth.
+/// Afterwards every visible pixel has a color of the palette.
+///
+/// # Arguments:
+/// * `img`: The image, already in its final size.
+/// * `depth`: The color depth.
+/// * `dither`: The dithering method.
+///
+pub fn apply(img: &mut RgbaImage, depth: ColorDepth, dither: Dither) {
+    if depth == ColorDepth::TrueColor {
+        return;
+    }
+    match dither {
+        Dither::None => {
+            for pixel in img.pixels_mut() {
+                let [r, g, b] = depth.nearest([pixel[0], pixel[1], pixel[2]]);
+                pixel.0 = [r, g, b, pixel[3]];
+            }
+        }
+        Dither::Ordered => {
+            // Roughly the distance between two colors of the palette.
+            let spread = match depth {
+                ColorDepth::Ansi256 => 40.0,
+                ColorDepth::Ansi16 => 128.0,
+                _ => 255.0,
+            };
+            for (x, y, pixel) in img.enumerate_pixels_mut() {
+                let offset = (BAYER[y as usize % 4][x as usize % 4] + 0.5) / 16.0 - 0.5;
+                let rgb = [pixel[0], pixel[1], pixel[2]]
+                    .map(|v| (v as f32 + offset * spread).clamp(0.0, 255.0) as u8);
+                let [r, g, b] = depth.nearest(rgb);
+                pixel.0 = [r, g, b, pixel[3]];
+            }
+        }
+        Dither::FloydSteinberg => {
+            let (w, h) = (img.width() as usize, img.height() as usize);
+            let mut buffer: Vec<[f32; 3]> = img
+                .pixels()
+                .map(|p| [p[0] as f32, p[1] as f32, p[2] as f32])
+                .collect();
+            for y in 0..h {
+                for x in 0..w {
+                    let pixel = img.get_pixel_mut(x as u32, y as u32);
+                    // Transparent pixels aren't drawn, so they don't take any error.
+                    if pixel[3] == 0 {
+                        continue;
+                    }
+                    let old = buffer[y * w + x].map(|v| v.clamp(0.0, 255.0));
+                    let new = depth.nearest(old.map(|v| v.round() as u8));
+                    pixel.0 = [new[0], new[1], new[2], pixel[3]];
+
+                    let error = [0, 1, 2].map(|i| old[i] - new[i] as f32);
+                    let mut spread = |dx: isize, dy: usize, weight: f32| {
+                        let nx = x as isize + dx;
+                        if nx < 0 || nx as usize >= w || y + dy >= h {
+                            return;
+                        }
+                        let target = &mut buffer[(y + dy) * w + nx as usize];
+                        for i in 0..3 {
+                            target[i] += error[i] * weight;
+                        }
+                    };
+                    spread(1, 0, 7.0 / 16.0);
+                    spread(-1, 1, 3.0 / 16.0);
+                    spread(0, 1, 5.0 / 16.0);
+                    spread(1, 1, 1.0 / 16.0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn gray(value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(8, 8, Rgba([value, value, value, 255]))
+    }
+
+    fn white_pixels(img: &RgbaImage) -> usize {
+        img.pixels().filter(|p| p[0] == 255).count()
+    }
+
+    #[test]
+    fn test_none() {
+        let mut img = gray(100);
+        apply(&mut img, ColorDepth::Mono, Dither::None);
+        assert_eq!(white_pixels(&img), 0);
+    }
+
+    #[test]
+    fn test_floyd_steinberg() {
+        // Half gray turns into about half white pixels.
+        let mut img = gray(128);
+        apply(&mut img, ColorDepth::Mono, Dither::FloydSteinberg);
+        assert!((28..=36).contains(&white_pixels(&img)));
+        assert!(img.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_ordered() {
+        let mut img = gray(128);
+        apply(&mut img, ColorDepth::Mono, Dither::Ordered);
+        assert_eq!(white_pixels(&img), 32);
+    }
+
+    #[test]
+    fn test_truecolor() {
+        let mut img = gray(123);
+        apply(&mut img, ColorDepth::TrueColor, Dither::FloydSteinberg);
+        assert_eq!(img, gray(123));
+    }
+}
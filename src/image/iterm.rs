@@ -0,0 +1,93 @@
+use std::fs;
+
+use crate::image::{cells, reserve};
+use crate::util::base64;
+use image::io::Reader;
+use image::ImageError;
+
+/// Renders images with the inline image protocol of iTerm2, also supported by WezTerm.
+/// https://iterm2.com/documentation-images.html
+pub struct Iterm;
+
+impl Iterm {
+    /// Process an image and send the file as it is, the terminal decodes it on its own.
+    /// # Parameters:
+    /// - `file`: Path to the image
+    /// - `height`: Height of the image in characters
+    pub fn process(file: &str, height: u32) -> String {
+        // Only the header is needed for the dimensions.
+        let dimensions = Reader::open(file)
+            .map_err(ImageError::from)
+            .and_then(|r| r.with_guessed_format().map_err(ImageError::from))
+            .and_then(|r| r.into_dimensions());
+        let (w, h) = match dimensions {
+            Ok(dimensions) => dimensions,
+            Err(e) => {
+                println!("{}", e);
+                return String::new();
+            }
+        };
+        let data = match fs::read(file) {
+            Ok(data) => data,
+            Err(e) => {
+                println!("{}", e);
+                return String::new();
+            }
+        };
+        let (cols, rows) = cells(w, h, height);
+        reserve(&Self::encode(&data, cols, rows), cols, rows)
+    }
+
+    /// Create the escape sequence drawing the image.
+    /// # Parameters:
+    /// - `data`: The content of the image file
+    /// - `cols`: Width of the image in cells
+    /// - `rows`: Height of the image in cells
+    ///
+    /// The image moves the cursor below itself, so the cursor gets saved and restored around it.
+    /// `doNotMoveCursor` does the same on newer versions, but isn't supported everywhere.
+    pub fn encode(data: &[u8], cols: u32, rows: u32) -> String {
+        format!(
+            "\x1b7\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1;doNotMoveCursor=1:{}\x07\x1b8",
+            data.len(),
+            cols,
+            rows,
+            base64::encode(data)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 PNG, red on the left and blue on the right.
+    const FIXTURE: [u8; 74] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x06, 0x00, 0x00, 0x00, 0x72,
+        0xb6, 0x0d, 0x24, 0x00, 0x00, 0x00, 0x11, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8,
+        0xcf, 0xc0, 0x00, 0x42, 0xff, 0x19, 0x60, 0x0c, 0x00, 0x43, 0xce, 0x07, 0xf9, 0x25, 0x02,
+        0xf9, 0xbe, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn test_process() {
+        let path = std::env::temp_dir().join("lightfetch_iterm_fixture.png");
+        fs::write(&path, FIXTURE).unwrap();
+        let out = Iterm::process(path.to_str().unwrap(), 2);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            out.as_bytes(),
+            "\x1b7\x1b]1337;File=inline=1;size=74;width=4;height=2;preserveAspectRatio=1;doNotMoveCursor=1:\
+             iVBORw0KGgoAAAANSUhEUgAAAAIAAAACCAYAAABytg0kAAAAEUlEQVR42mP4z8AAQv8ZYAwAQ84H+SUC+b4AAAAASUVORK5CYII=\
+             \x07\x1b8    \n    \n"
+                .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_process_missing() {
+        assert_eq!(Iterm::process("/nonexistent/lightfetch.png", 2), "");
+    }
+}
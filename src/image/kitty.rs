@@ -0,0 +1,100 @@
+use std::io::Cursor;
+
+use crate::image::{cells, reserve};
+use crate::util::base64;
+use image::io::Reader;
+use image::{DynamicImage, ImageError, ImageOutputFormat};
+
+/// The kitty graphics protocol only allows 4096 bytes of payload per escape sequence.
+const CHUNK_SIZE: usize = 4096;
+
+/// Renders images with the kitty graphics protocol, in the full resolution of the picture.
+/// https://sw.kovidgoyal.net/kitty/graphics-protocol/
+pub struct Kitty;
+
+impl Kitty {
+    /// Process an image and transmit it as PNG.
+    /// # Parameters:
+    /// - `file`: Path to the image
+    /// - `height`: Height of the image in characters
+    pub fn process(file: &str, height: u32) -> String {
+        let img = match Reader::open(file)
+            .map_err(ImageError::from)
+            .and_then(|r| r.decode())
+        {
+            Ok(img) => img,
+            Err(e) => {
+                println!("{}", e);
+                return String::new();
+            }
+        };
+        let (cols, rows) = cells(img.width(), img.height(), height);
+        match Self::png(&img) {
+            Ok(png) => reserve(&Self::encode(&png, cols, rows), cols, rows),
+            Err(e) => {
+                println!("{}", e);
+                String::new()
+            }
+        }
+    }
+
+    /// Encode an image as PNG.
+    fn png(img: &DynamicImage) -> Result<Vec<u8>, ImageError> {
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+        Ok(png)
+    }
+
+    /// Create the escape sequences transmitting and placing the PNG data.
+    /// # Parameters:
+    /// - `png`: The PNG data
+    /// - `cols`: Width of the placement in cells
+    /// - `rows`: Height of the placement in cells
+    ///
+    /// The image gets scaled into the given cells. `C=1` keeps the cursor in place
+    /// and `q=2` stops the terminal from answering, which would end up in the shell otherwise.
+    pub fn encode(png: &[u8], cols: u32, rows: u32) -> String {
+        let data = base64::encode(png);
+        let chunks: Vec<&[u8]> = data.as_bytes().chunks(CHUNK_SIZE).collect();
+        let mut output = String::with_capacity(data.len() + chunks.len() * 16 + 64);
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = u8::from(i + 1 < chunks.len());
+            if i == 0 {
+                output.push_str(&format!(
+                    "\x1b_Ga=T,f=100,t=d,c={},r={},C=1,q=2,m={};",
+                    cols, rows, more
+                ));
+            } else {
+                output.push_str(&format!("\x1b_Gm={};", more));
+            }
+            // Base64 is plain ASCII, so this can't fail.
+            output.push_str(std::str::from_utf8(chunk).unwrap());
+            output.push_str("\x1b\\");
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_single() {
+        let out = Kitty::encode(b"foo", 4, 2);
+        assert_eq!(out, "\x1b_Ga=T,f=100,t=d,c=4,r=2,C=1,q=2,m=0;Zm9v\x1b\\");
+    }
+
+    #[test]
+    fn test_encode_chunked() {
+        // 3 bytes turn into 4 base64 characters, so this is exactly two and a half chunks.
+        let png = vec![0u8; CHUNK_SIZE / 4 * 3 * 5 / 2];
+        let out = Kitty::encode(&png, 10, 5);
+        let parts: Vec<&str> = out.split("\x1b\\").filter(|s| !s.is_empty()).collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with("\x1b_Ga=T,f=100,t=d,c=10,r=5,C=1,q=2,m=1;"));
+        assert!(parts[1].starts_with("\x1b_Gm=1;"));
+        assert!(parts[2].starts_with("\x1b_Gm=0;"));
+        assert_eq!(parts[1].len(), "\x1b_Gm=1;".len() + CHUNK_SIZE);
+    }
+}
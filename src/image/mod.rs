@@ -1,22 +1,196 @@
-use std::borrow::Borrow;
-use crate::config::cache::Image;
+use crate::builder::create_spaces;
+use crate::image::dither::Dither;
+use crate::util::color::ColorDepth;
+use image::imageops::FilterType;
 
 mod ascii;
 mod default;
+pub mod dither;
 mod iterm;
 mod kitty;
+mod sixel;
+
+/// All `[ART] mode` values that are rendered from an image file.
+pub const MODES: [&str; 5] = ["image", "kitty", "iterm", "sixel", "characters"];
 
 pub struct ImageBuilder {
-    pub image: Image,
+    pub path: String,
+    pub size: u32,
+    pub filter: FilterType,
+    pub depth: ColorDepth,
+    pub dither: Dither,
+    pub characters: String,
+    pub edges: bool,
+    pub tint: ColorDepth,
 }
 
 impl ImageBuilder {
-    pub fn init(image: Image) -> Self {
-        Self { image }
+    /// Create a new `ImageBuilder` for the given image.
+    ///
+    /// # Arguments:
+    /// * `path`: Path to the image.
+    /// * `size`: Height of the art in characters.
+    /// * `filter`: The filter used for resizing.
+    ///
+    /// # Returns:
+    /// A new `ImageBuilder` instance.
+    ///
+    pub fn init(path: &str, size: u32, filter: FilterType) -> Self {
+        Self {
+            path: path.to_string(),
+            size,
+            filter,
+            depth: ColorDepth::TrueColor,
+            dither: Dither::None,
+            characters: " .:-=+*%@".to_string(),
+            edges: false,
+            tint: ColorDepth::Mono,
+        }
+    }
+
+    /// Set the colors of the `image` mode.
+    ///
+    /// # Arguments:
+    /// * `depth`: How many colors we are allowed to use.
+    /// * `dither`: How the colors are reduced to the color depth.
+    ///
+    /// # Returns:
+    /// The `ImageBuilder` with the colors set.
+    ///
+    pub fn set_colors(self, depth: ColorDepth, dither: Dither) -> Self {
+        Self {
+            depth,
+            dither,
+            ..self
+        }
     }
+
+    /// Set the options of the `characters` mode.
+    ///
+    /// # Arguments:
+    /// * `characters`: The characters from dark to bright.
+    /// * `edges`: Draw edges with line characters?
+    /// * `tint`: Color the characters with the color of the pixels.
+    ///
+    /// # Returns:
+    /// The `ImageBuilder` with the options set.
+    ///
+    pub fn set_characters(self, characters: &str, edges: bool, tint: ColorDepth) -> Self {
+        Self {
+            characters: characters.to_string(),
+            edges,
+            tint,
+            ..self
+        }
+    }
+
+    /// Describe everything that changes the output of `get_mode`, except for the image itself.
+    /// Used to tell cached art apart.
+    ///
+    /// # Arguments:
+    /// * `mode`: The art mode, see `MODES`.
+    ///
+    /// # Returns:
+    /// The parameters as a String.
+    ///
+    pub fn params(&self, mode: &str) -> String {
+        format!(
+            "mode={};size={};filter={:?};depth={:?};dither={:?};characters={};edges={};tint={:?}",
+            mode,
+            self.size,
+            self.filter,
+            self.depth,
+            self.dither,
+            self.characters,
+            self.edges,
+            self.tint
+        )
+    }
+
+    /// Render the image with the renderer of the given `[ART] mode`.
+    /// Falls back to the half-block renderer.
+    ///
+    /// # Arguments:
+    /// * `mode`: The art mode, see `MODES`.
+    ///
+    /// # Returns:
+    /// The art as a String, ready to be put next to the modules.
+    ///
     pub fn get_mode(&self, mode: &str) -> String {
         match mode {
-            _ => default::Default::new(self.image).
+            "kitty" => kitty::Kitty::process(&self.path, self.size),
+            "iterm" => iterm::Iterm::process(&self.path, self.size),
+            "sixel" => sixel::Sixel::process(&self.path, self.size, self.filter),
+            "characters" => ascii::Ascii::new(&self.characters, self.edges, self.tint).process(
+                &self.path,
+                self.size,
+                self.filter,
+            ),
+            _ => default::Default::process(
+                &self.path,
+                self.size,
+                self.filter,
+                self.depth,
+                self.dither,
+            ),
         }
     }
 }
+
+/// Calculate how many terminal cells an image takes up.
+/// Cells are roughly twice as high as they are wide, which is what the half-block renderer relies on.
+///
+/// # Arguments:
+/// * `width`: Width of the image in pixels.
+/// * `height`: Height of the image in pixels.
+/// * `size`: Height of the art in characters.
+///
+/// # Returns:
+/// The columns and rows.
+///
+fn cells(width: u32, height: u32, size: u32) -> (u32, u32) {
+    ((2 * width * size / height.max(1)).max(1), size.max(1))
+}
+
+/// Reserve the space for an image drawn by the terminal itself.
+/// The escape sequence goes in front of the first line, followed by blank lines of the image size,
+/// so the `Builder` puts the modules right next to the picture.
+///
+/// # Arguments:
+/// * `escape`: The escape sequence drawing the image, must not move the cursor.
+/// * `cols`: Width of the image in cells.
+/// * `rows`: Height of the image in cells.
+///
+/// # Returns:
+/// The art as a String.
+///
+fn reserve(escape: &str, cols: u32, rows: u32) -> String {
+    let blank = create_spaces(cols as usize);
+    let mut output = String::with_capacity(escape.len() + (blank.len() + 1) * rows as usize);
+    output.push_str(escape);
+    for _ in 0..rows {
+        output.push_str(&blank);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::hyperstr::Ansi;
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(cells(100, 100, 15), (30, 15));
+        assert_eq!(cells(50, 100, 10), (10, 10));
+        assert_eq!(cells(1, 1000, 1), (1, 1));
+    }
+
+    #[test]
+    fn test_reserve() {
+        let out = reserve("\x1b_Ga=T;\x1b\\", 3, 2);
+        assert!(out.starts_with("\x1b_Ga=T;\x1b\\"));
+        assert_eq!(out.strip_ansi_colors(), "   \n   \n");
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::image::reserve;
+use crate::util::terminal::size::{cell_size, DEFAULT_CELL_SIZE};
+use image::imageops::resize;
+use image::imageops::FilterType;
+use image::io::Reader;
+use image::{ImageError, RgbaImage};
+
+/// Sixel supports up to 256 color registers on pretty much every terminal.
+const MAX_COLORS: usize = 256;
+
+/// Pixels below this alpha value are left transparent.
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// Renders images as sixel graphics, supported by xterm, foot, mlterm and others.
+/// https://vt100.net/docs/vt3xx-gp/chapter14.html
+pub struct Sixel;
+
+impl Sixel {
+    /// Process an image and encode it as sixel.
+    /// # Parameters:
+    /// - `file`: Path to the image
+    /// - `height`: Height of the image in characters
+    /// - `filter`: The filter used for resizing
+    pub fn process(file: &str, height: u32, filter: FilterType) -> String {
+        let img = match Reader::open(file)
+            .map_err(ImageError::from)
+            .and_then(|r| r.decode())
+        {
+            Ok(img) => img.to_rgba8(),
+            Err(e) => {
+                println!("{}", e);
+                return String::new();
+            }
+        };
+        let (cell_w, cell_h) = cell_size().unwrap_or(DEFAULT_CELL_SIZE);
+        let rows = height.max(1);
+        let pixel_h = rows * cell_h;
+        let pixel_w = (img.width() * pixel_h / img.height().max(1)).max(1);
+        let cols = pixel_w.div_ceil(cell_w);
+        let img = resize(&img, pixel_w, pixel_h, filter);
+        // The cursor ends up below the image, so it gets saved and restored around it.
+        let escape = format!("\x1b7{}\x1b8", Self::encode(&img));
+        reserve(&escape, cols, rows)
+    }
+
+    /// Encode an image as a sixel sequence.
+    /// Transparent pixels are skipped and keep the background of the terminal.
+    /// # Parameters:
+    /// - `img`: The image, already in its final size
+    pub fn encode(img: &RgbaImage) -> String {
+        let opaque: Vec<[u8; 3]> = img
+            .pixels()
+            .filter(|p| p[3] >= ALPHA_THRESHOLD)
+            .map(|p| [p[0], p[1], p[2]])
+            .collect();
+        let palette = quantize(&opaque, MAX_COLORS);
+
+        // Map every pixel to its color register.
+        let mut lookup: HashMap<[u8; 3], usize> = HashMap::new();
+        let indices: Vec<Option<usize>> = img
+            .pixels()
+            .map(|p| {
+                if p[3] < ALPHA_THRESHOLD {
+                    return None;
+                }
+                let color = [p[0], p[1], p[2]];
+                Some(
+                    *lookup
+                        .entry(color)
+                        .or_insert_with(|| nearest(&palette, color)),
+                )
+            })
+            .collect();
+
+        let (w, h) = (img.width() as usize, img.height() as usize);
+        let mut output = String::new();
+        // P2 = 1: Pixels without a color stay transparent.
+        write!(output, "\x1bP0;1;0q\"1;1;{};{}", w, h).unwrap();
+        for (i, c) in palette.iter().enumerate() {
+            write!(
+                output,
+                "#{};2;{};{};{}",
+                i,
+                percent(c[0]),
+                percent(c[1]),
+                percent(c[2])
+            )
+            .unwrap();
+        }
+
+        for band in (0..h).step_by(6) {
+            // Collect the sixels of every color used in this band.
+            let mut colors: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+            for y in band..(band + 6).min(h) {
+                for x in 0..w {
+                    if let Some(i) = indices[y * w + x] {
+                        colors.entry(i).or_insert_with(|| vec![0; w])[x] |= 1 << (y - band);
+                    }
+                }
+            }
+
+            for (n, (i, sixels)) in colors.iter().enumerate() {
+                if n > 0 {
+                    // Carriage return, the next color draws over the same band.
+                    output.push('$');
+                }
+                write!(output, "#{}", i).unwrap();
+                push_sixels(&mut output, sixels);
+            }
+            if band + 6 < h {
+                // Next band.
+                output.push('-');
+            }
+        }
+        output.push_str("\x1b\\");
+        output
+    }
+}
+
+/// Append a row of sixels with run-length encoding.
+fn push_sixels(output: &mut String, sixels: &[u8]) {
+    // Empty sixels at the end don't draw anything.
+    let len = sixels.iter().rposition(|&s| s != 0).map_or(0, |p| p + 1);
+    let mut i = 0;
+    while i < len {
+        let sixel = sixels[i];
+        let mut run = 1;
+        while i + run < len && sixels[i + run] == sixel {
+            run += 1;
+        }
+        let c = (63 + sixel) as char;
+        if run > 3 {
+            write!(output, "!{}{}", run, c).unwrap();
+        } else {
+            for _ in 0..run {
+                output.push(c);
+            }
+        }
+        i += run;
+    }
+}
+
+/// Sixel colors are in percent instead of 0-255.
+fn percent(value: u8) -> u32 {
+    (value as u32 * 100 + 127) / 255
+}
+
+/// Reduce the colors to a palette with median cut.
+/// If there are few enough colors they are used as they are.
+///
+/// # Arguments:
+/// * `pixels`: The colors of all visible pixels.
+/// * `max`: The maximum size of the palette.
+///
+/// # Returns:
+/// The palette.
+///
+fn quantize(pixels: &[[u8; 3]], max: usize) -> Vec<[u8; 3]> {
+    let mut unique: Vec<[u8; 3]> = pixels.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+    if unique.len() <= max {
+        return unique;
+    }
+
+    let mut boxes: Vec<Vec<[u8; 3]>> = vec![pixels.to_vec()];
+    while boxes.len() < max {
+        // Split the box with the widest channel range.
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (channel, range) = widest_channel(b);
+                (i, channel, range)
+            })
+            .max_by_key(|&(_, _, range)| range);
+        let (i, channel, range) = match widest {
+            Some(widest) => widest,
+            None => break,
+        };
+        if range == 0 {
+            break;
+        }
+        let mut colors = boxes.swap_remove(i);
+        colors.sort_unstable_by_key(|c| c[channel]);
+        let upper = colors.split_off(colors.len() / 2);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|b| {
+            let mut sum = [0u64; 3];
+            for c in b {
+                for (s, v) in sum.iter_mut().zip(c) {
+                    *s += *v as u64;
+                }
+            }
+            let len = b.len() as u64;
+            [
+                (sum[0] / len) as u8,
+                (sum[1] / len) as u8,
+                (sum[2] / len) as u8,
+            ]
+        })
+        .collect()
+}
+
+/// Get the channel with the largest range of values and the range itself.
+fn widest_channel(colors: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = colors.iter().map(|c| c[channel]).min().unwrap_or(0);
+            let max = colors.iter().map(|c| c[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap()
+}
+
+/// Find the closest color of the palette.
+fn nearest(palette: &[[u8; 3]], color: [u8; 3]) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, p)| {
+            p.iter()
+                .zip(color.iter())
+                .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+                .sum::<i32>()
+        })
+        .map_or(0, |(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_encode() {
+        // Red on the left, blue on the right and a transparent pixel in the bottom right.
+        let mut img = RgbaImage::new(2, 2);
+        img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        img.put_pixel(0, 1, Rgba([255, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([0, 0, 255, 255]));
+        img.put_pixel(1, 1, Rgba([0, 0, 255, 0]));
+        assert_eq!(
+            Sixel::encode(&img),
+            "\x1bP0;1;0q\"1;1;2;2#0;2;0;0;100#1;2;100;0;0#0?@$#1B\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_run_length() {
+        let mut out = String::new();
+        push_sixels(&mut out, &[1, 1, 1, 1, 1, 2, 2, 0, 0]);
+        assert_eq!(out, "!5@AA");
+    }
+
+    #[test]
+    fn test_quantize() {
+        let pixels: Vec<[u8; 3]> = (0..=255).flat_map(|v| [[v, 0, 0], [0, v, 0]]).collect();
+        let palette = quantize(&pixels, 16);
+        assert_eq!(palette.len(), 16);
+        assert_eq!(quantize(&[[1, 2, 3], [1, 2, 3]], 16), vec![[1, 2, 3]]);
+    }
+}
@@ -4,20 +4,31 @@ extern crate core;
 
 use crate::args::{Args, Argument};
 use crate::builder::{create_spaces, Builder};
-use crate::config::cache::Cache;
+use crate::config::cache::command;
+use crate::config::cache::variables::Variables;
+use crate::config::cache::{Cache, Meta};
+use crate::config::helio::schema::Issue;
 use crate::config::helio::Helio;
 use crate::data::FetchData;
-use crate::image::process_image;
+use crate::fetch::disk::DiskFilter;
+use crate::fetch::packages::MANAGERS;
+use crate::fetch::uptime::UptimeFormat;
+use crate::image::ImageBuilder;
 use crate::modules::module::FetchModule;
 use crate::modules::FetchModules;
+use crate::util::color::ColorDepth;
 use crate::util::constants::CATS;
 use crate::util::hyperstr::{ascii_check, Ansi};
-use crate::util::mth::to_vector;
+use crate::util::mth::{to_vector, SizeFormat};
+use crate::util::terminal::detect::{color_depth, Graphics};
 use crate::util::unicode::Unicode;
 use crate::variable::creator::Creator;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::Path;
+use std::process::exit;
+use std::time::Duration;
 
 mod args;
 mod builder;
@@ -43,7 +54,7 @@ mod welcome;
 /// Github: https://github.com/bwte/lightfetch/issues
 /// #
 /// Made with ❤️ by bwte#6092
-
+///
 /// This function is the main entry point for [lightfetch].
 /// Amazing, right?
 ///
@@ -72,9 +83,24 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         0,
         "Display the beautiful welcome message once again.",
     );
+    arg_builder.add(
+        "--cache",
+        1,
+        "Manage the cache: list, clear, prune or stats.",
+    );
+    arg_builder.add(
+        "--check-config",
+        0,
+        "Check the config for errors without running the fetch.",
+    );
 
     let mut cfg = util::data::get_env("HOME").unwrap();
     cfg.push_str("/.config/lightfetch/config.ini");
+    // Use the TOML config if that's the one there is.
+    let toml = cfg.replace(".ini", ".toml");
+    if !Path::new(&cfg).exists() && Path::new(&toml).exists() {
+        cfg = toml;
+    }
 
     arg_builder.add_arg(
         Argument::new()
@@ -84,6 +110,18 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
             .count(1)
             .help("Run lightfetch with a custom config file location."),
     );
+    arg_builder.add_arg(
+        Argument::new()
+            .alias("--set")
+            .count(1)
+            .repeatable()
+            .help("Override a config key, e.g. --set ART.mode=ascii. Can be used multiple times."),
+    );
+    arg_builder.add(
+        "--save",
+        0,
+        "Write the keys changed with --set to the config file.",
+    );
 
     let flash_args = arg_builder.parse(None);
     // if let Some(err) = flash_args.as_ref() {
@@ -92,14 +130,50 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     // }
     let args = flash_args.unwrap();
 
-    let cfg_arg = args.get("--config");
-    if let Some(..) = cfg_arg {
-        cfg = cfg_arg.unwrap().val().get(0).unwrap().to_string();
+    if let Some(cfg_arg) = args.get("--config") {
+        cfg = cfg_arg.val().first().unwrap().to_string();
+    }
+
+    if args.has("--check-config") {
+        // Dummy data is enough, only the names of the placeholders matter.
+        let path = Path::new(&cfg);
+        // A config that can't be read at all is reported like any other issue.
+        let issues = config::check::check_config(
+            path,
+            &variable_map(
+                &FetchData::new(),
+                &color_map(&Helio::new(), ColorDepth::Mono),
+            ),
+        )
+        .unwrap_or_else(|why| {
+            vec![Issue {
+                file: None,
+                line: 1,
+                column: 1,
+                message: why.message,
+                level: why.level,
+            }]
+        });
+        config::check::report(path, &issues);
+        exit(if issues.is_empty() { 0 } else { 1 });
     }
 
     // Create a config instance!
     let mut config = Helio::new();
-    config.load(cfg.as_str()).unwrap();
+    if let Err(why) = config.load(cfg.as_str()) {
+        eprint!("{}", why);
+        exit(1);
+    }
+    // Environment variables first, so --set wins.
+    config.override_env(std::env::vars()).unwrap();
+    if let Some(set_arg) = args.get("--set") {
+        for value in set_arg.val() {
+            config.override_str(value).unwrap();
+        }
+    }
+    if args.has("--save") {
+        config.save(cfg.as_str()).unwrap();
+    }
 
     if args.has("--version") {
         version::show_version();
@@ -111,8 +185,77 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut mds = FetchModules::new();
 
+    let cache_dir = config
+        .get_str("CACHE", "^ path")
+        .unwrap()
+        .replace('~', &util::data::get_env("HOME").unwrap());
+
+    // Reuse the values that barely change, if they are still valid.
+    let cache_variables = config.get_bool("CACHE", "enable").unwrap()
+        && config.get_bool("CACHE", "^ variables").unwrap();
+    let variables = Variables::new(
+        Path::new(&cache_dir),
+        config
+            .get_float("CACHE", "recache variables if older than x hours")
+            .unwrap(),
+    );
+    if let Some(cache_arg) = args.get("--cache") {
+        let mut cache = Cache::new(config.clone(), &cache_dir);
+        command::run(cache_arg.val().first().unwrap(), &mut cache, &variables).unwrap();
+        exit(0);
+    }
+
+    let cached = if cache_variables {
+        variables.load()
+    } else {
+        None
+    };
+
+    let memory = SizeFormat::new(
+        config.get_int("MEMORY", "rounding").unwrap() as usize,
+        config.get_enum("MEMORY", "units").unwrap(),
+        ["kb", "mb", "gb", "tb"].map(|key| config.get_str("MEMORY", key).unwrap()),
+    );
+    let uptime = UptimeFormat {
+        suffix: config.get_bool("UPTIME", "suffix").unwrap(),
+        plural: if config.get_bool("UPTIME", "^ plurals").unwrap() {
+            Some(config.get_str("UPTIME", "^ character").unwrap())
+        } else {
+            None
+        },
+        hide_zero: config.get_bool("UPTIME", "hide if zero").unwrap(),
+        units: ["day", "hour", "minute", "second"]
+            .map(|key| config.get_str("UPTIME", key).unwrap()),
+    };
+
+    // Measuring takes a moment, only do it if the usage is shown.
+    let interval = config.get_int("CPU", "sample interval").unwrap();
+    let sample = if interval > 0
+        && config
+            .get_str("FETCH", "text")
+            .unwrap()
+            .to_uppercase()
+            .contains("CPU_USAGE")
+    {
+        Some(Duration::from_millis(interval as u64))
+    } else {
+        None
+    };
+
+    let show = config.get_list("DISK", "show").unwrap();
+    let disk = DiskFilter {
+        show: if show == ["auto"] { Vec::new() } else { show },
+        hide_types: config.get_list("DISK", "hide types").unwrap(),
+    };
+
     // Start getting the data. (Running in parallel)
-    let fetch_data = prepare_data();
+    let (fetch_data, restored) = prepare_data(cached.as_ref(), &memory, &uptime, sample, &disk);
+    // A cache with missing values is replaced as well.
+    if cache_variables && !restored {
+        if let Err(e) = variables.store(&fetch_data.cached()) {
+            eprint!("{}", e);
+        }
+    }
 
     // Get the fetch String.
     let fetch = to_vector(config.get_str("FETCH", "text").unwrap());
@@ -121,7 +264,24 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut art_raw = String::new();
 
     // Art mode.
-    let art_mode = config.get_str("ART", "mode").unwrap().to_lowercase();
+    let mut art_mode = config.get_str("ART", "mode").unwrap().to_lowercase();
+    let mut depth = config.get_enum("IMAGE", "color depth").unwrap();
+    let mut tint = config.get_enum("IMAGE", "^ tint").unwrap();
+
+    // Let the terminal decide what it can display.
+    if art_mode == "auto" {
+        let timeout = if config.get_bool("ART", "^ query terminal").unwrap() {
+            Some(Duration::from_millis(
+                config.get_int("ART", "^ query timeout").unwrap() as u64,
+            ))
+        } else {
+            None
+        };
+        let graphics = Graphics::detect(timeout);
+        art_mode = graphics.mode().to_string();
+        depth = graphics.depth();
+        tint = graphics.depth();
+    }
 
     // Art path.
     let cfg = config
@@ -130,40 +290,41 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         .replace('~', &util::data::get_env("HOME").unwrap());
 
     // Determine if we use image or ascii mode.
-    if art_mode == "image" {
+    if image::MODES.contains(&art_mode.as_str()) {
         // Image mode is enabled.
         let size = config.get_int("IMAGE", "size").unwrap();
-        let filter = config.get_filter("IMAGE", "filter").unwrap();
-
-        let cache = Cache::new(
-            config.clone(),
-            config
-                .get_str("CACHE", "^ path")
-                .unwrap()
-                .replace('~', &util::data::get_env("HOME").unwrap())
-                .as_str(),
-        );
-        let hash = util::data::get_fake_hash(
-            Path::new(cfg.as_str()),
-            size,
-            config.get_str("IMAGE", "filter").unwrap(),
-        );
-        if config.get_bool("CACHE", "enable").unwrap() && cache.exists(hash.clone()) {
-            // Image is in cache.
-            let image = cache.get(hash).unwrap();
-            art_raw = image.get_data().to_string();
+        let filter = config.get_enum("IMAGE", "filter").unwrap();
+
+        let mut cache = Cache::new(config.clone(), &cache_dir);
+        let builder = ImageBuilder::init(cfg.as_str(), size, filter)
+            .set_colors(depth, config.get_enum("IMAGE", "^ dither").unwrap())
+            .set_characters(
+                &config.get_str("IMAGE", "characters").unwrap(),
+                config.get_bool("IMAGE", "^ edges").unwrap(),
+                tint,
+            );
+        let params = builder.params(&art_mode);
+        // Without a hash the image can't be cached, the renderer reports why.
+        let hash = if config.get_bool("CACHE", "enable").unwrap()
+            && config.get_bool("CACHE", "^ images").unwrap()
+        {
+            util::data::get_hash(Path::new(cfg.as_str()), &params).ok()
         } else {
-            // Image is not in cache.
-            let img = process_image(cfg.as_str(), size, filter);
-            art_raw = img.clone();
-            if config.get_bool("CACHE", "enable").unwrap() {
-                // Cache image.
-                let mut path = util::data::get_env("HOME").unwrap();
-                path.push_str("/.config/lightfetch/cache/");
-                path.push_str(&hash);
-                let mut w = File::create(path).unwrap();
-                w.write_all(img.as_ref()).unwrap();
-                w.flush().unwrap();
+            None
+        };
+
+        match hash.clone().and_then(|hash| cache.get(hash)) {
+            // Image is in cache.
+            Some(image) => art_raw = image.get_data().to_string(),
+            None => {
+                // Image is not in cache.
+                art_raw = builder.get_mode(&art_mode);
+                // Failed renders come back empty and shouldn't stick around.
+                if let Some(hash) = hash.filter(|_| !art_raw.is_empty()) {
+                    if let Err(e) = cache.insert(hash, art_raw.clone(), Meta::new(&cfg, &params)) {
+                        eprint!("{}", e);
+                    }
+                }
             }
         }
     }
@@ -220,15 +381,16 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Check if the art is centered. (Again)
     ascii_check(should_center, &mut art, fetch_lines, art_lines);
-    let mut modules = modules;
 
     if config // If variables are enabled.
         .get_bool("GENERAL", "enable variables")
         .unwrap()
     {
+        let colors = color_map(&config, color_depth());
         modules = variable_creator(
             modules,
             fetch_data,
+            &colors,
             config.get_str("GENERAL", "^ prefix").unwrap(),
             config.get_str("GENERAL", "^ suffix").unwrap(),
         );
@@ -331,12 +493,76 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// # Arguments:
 /// * `String`: The String to replace the placeholders in.
 /// * `data`: The FetchData struct to get the data from.
+/// * `colors`: The color placeholders and their escape sequences.
 /// # Returns:
 /// The String with the placeholders replaced.
 ///
-fn variable_creator(target: String, mds: FetchData, prefix: String, suffix: String) -> String {
+fn variable_creator(
+    target: String,
+    mds: FetchData,
+    colors: &HashMap<&'static str, String>,
+    prefix: String,
+    suffix: String,
+) -> String {
+    let val = variable_map(&mds, colors);
+
+    let string = target;
+
+    let creator = Creator::init(&string, prefix.as_str(), suffix.as_str());
+
+    creator.process_variables(&val).unwrap()
+}
+
+/// The placeholders of each GPU: its name, driver and video memory.
+const GPU_VARIABLES: [[&str; 3]; 4] = [
+    ["GPU_1", "GPU_1_DRIVER", "GPU_1_VRAM"],
+    ["GPU_2", "GPU_2_DRIVER", "GPU_2_VRAM"],
+    ["GPU_3", "GPU_3_DRIVER", "GPU_3_VRAM"],
+    ["GPU_4", "GPU_4_DRIVER", "GPU_4_VRAM"],
+];
+
+/// The sections with a `color` key and the placeholder of that color.
+const MODULE_COLORS: [[&str; 2]; 4] = [
+    ["CPU", "CPU_COLOR"],
+    ["MEMORY", "MEMORY_COLOR"],
+    ["UPTIME", "UPTIME_COLOR"],
+    ["DISK", "DISK_COLOR"],
+];
+
+/// The color placeholders: the basic colors and the `MODULE_COLORS` of the config.
+///
+/// # Arguments:
+/// * `config`: The config with the module colors, missing ones stay empty.
+/// * `depth`: How many colors the terminal can show, other colors are turned into the closest one.
+/// # Returns:
+/// The placeholders and their escape sequences.
+///
+fn color_map(config: &Helio, depth: ColorDepth) -> HashMap<&'static str, String> {
+    let mut colors = util::hyperstr::colormap(depth);
+    for [section, name] in MODULE_COLORS {
+        let color = config.get_color(section, "color");
+        colors.insert(name, color.map_or(String::new(), |color| color.fg(depth)));
+    }
+    colors
+}
+
+/// All placeholders that can be used in the fetch text, together with their values.
+///
+/// # Arguments:
+/// * `mds`: The FetchData struct to get the data from.
+/// * `colors`: The color placeholders and their escape sequences, see `color_map`.
+/// # Returns:
+/// The placeholders and their values.
+///
+fn variable_map<'a>(
+    mds: &'a FetchData,
+    colors: &'a HashMap<&'static str, String>,
+) -> HashMap<&'a str, &'a str> {
     // TODO: BACKGROUND VALUES? cba rn.
-    let mut val = util::hyperstr::colormap();
+    let mut val: HashMap<&str, &str> = colors
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect();
 
     // Replace the placeholders with the data.
     val.insert("USERNAME", &mds.user.username);
@@ -349,41 +575,114 @@ fn variable_creator(target: String, mds: FetchData, prefix: String, suffix: Stri
     val.insert("KERNEL", &mds.distro.kernel);
     val.insert("SHELL", &mds.shell.shell);
     val.insert("TERMINAL", &mds.terminal.terminal);
-    val.insert("PACKAGES", &mds.packages.pacman);
-    val.insert("UPTIME", &mds.uptime.uptime_raw);
+    val.insert("PACKAGES", &mds.packages.summary);
+    val.insert("PACKAGES_TOTAL", &mds.packages.total);
+    for ((_, name), count) in MANAGERS.iter().zip(&mds.packages.counts) {
+        val.insert(name, count);
+    }
+    val.insert("UPTIME", &mds.uptime.pretty);
+    val.insert("UPTIME_RAW", &mds.uptime.uptime_raw);
+    val.insert("UPTIME_DAYS", &mds.uptime.days);
+    val.insert("UPTIME_HOURS", &mds.uptime.hours);
+    val.insert("UPTIME_MINUTES", &mds.uptime.minutes);
+    val.insert("UPTIME_SECONDS", &mds.uptime.seconds);
+    val.insert("UPTIME_HOURS_PADDED", &mds.uptime.z_hours);
+    val.insert("UPTIME_MINUTES_PADDED", &mds.uptime.z_minutes);
+    val.insert("UPTIME_SECONDS_PADDED", &mds.uptime.z_seconds);
+    val.insert("MEMORY_USED", &mds.memory.used);
+    val.insert("MEMORY_TOTAL", &mds.memory.total);
+    val.insert("MEMORY_FREE", &mds.memory.free);
+    val.insert("MEMORY_AVAILABLE", &mds.memory.avail);
+    val.insert("MEMORY_PERCENT", &mds.memory.percent);
+    val.insert("SWAP_USED", &mds.memory.swap_used);
+    val.insert("SWAP_TOTAL", &mds.memory.swap_total);
+    val.insert("SWAP_FREE", &mds.memory.swap_free);
+    val.insert("SWAP_PERCENT", &mds.memory.swap_percent);
     val.insert("CPU_MODEL", &mds.cpu.model_name);
+    val.insert("CPU_VENDOR", &mds.cpu.vendor);
+    val.insert("CPU_SOCKETS", &mds.cpu.sockets);
     val.insert("CPU_CORES", &mds.cpu.cores);
+    val.insert("CPU_THREADS", &mds.cpu.threads);
+    val.insert("CPU_FREQ", &mds.cpu.max_ghz);
+    val.insert("CPU_FREQ_CURRENT", &mds.cpu.ghz);
+    val.insert("CPU_FAMILY", &mds.cpu.family);
+    val.insert("CPU_STEPPING", &mds.cpu.stepping);
+    val.insert("CPU_CACHE", &mds.cpu.cache_size);
+    val.insert("CPU_BOGOMIPS", &mds.cpu.bogomips);
+    val.insert("CPU_USAGE", &mds.cpu.cpu_load);
+    val.insert("CPU_USAGE_CORES", &mds.cpu.core_loads);
+    val.insert("LOADAVG_1", &mds.cpu.loadavg[0]);
+    val.insert("LOADAVG_5", &mds.cpu.loadavg[1]);
+    val.insert("LOADAVG_15", &mds.cpu.loadavg[2]);
+    val.insert("GPU", &mds.gpu);
+    val.insert("GPU_COUNT", &mds.gpu_count);
+    // Unused slots stay empty, so the text doesn't depend on the machine.
+    for (i, [name, driver, vram]) in GPU_VARIABLES.iter().enumerate() {
+        let gpu = mds.gpus.get(i);
+        val.insert(name, gpu.map_or("", |gpu| &gpu.name));
+        val.insert(driver, gpu.map_or("", |gpu| &gpu.driver));
+        val.insert(vram, gpu.map_or("", |gpu| &gpu.vram));
+    }
+    val.insert("DISK", &mds.disk);
+    for (name, value) in &mds.disk_variables {
+        val.insert(name, value);
+    }
     val.insert("FILL", "{FILL}");
     val.insert("IGNORE", "{IGNORE}");
-
-    let string = target;
-
-    let creator = Creator::init(&string, prefix.as_str(), suffix.as_str());
-
-    creator.process_variables(&val).unwrap()
+    val
 }
 
 /// Prepare the Data for the fetch.
 ///
+/// # Arguments:
+/// * `cached`: The values from the variable cache, these don't get fetched again.
+/// * `memory`: How the memory sizes are formatted.
+/// * `uptime`: How the uptime is formatted.
+/// * `sample`: How long to measure the cpu usage, `None` to skip it.
+/// * `disk`: Which mounts are shown.
+///
 /// # Returns:
-/// The FetchData struct with the data.
+/// The FetchData struct with the data, and whether the cached values could be used.
 ///
-fn prepare_data() -> FetchData {
+fn prepare_data(
+    cached: Option<&HashMap<String, String>>,
+    memory: &SizeFormat,
+    uptime: &UptimeFormat,
+    sample: Option<Duration>,
+    disk: &DiskFilter,
+) -> (FetchData, bool) {
     let mut data = FetchData::new();
+    let restored = cached.is_some_and(|values| data.restore(values));
     // TODO: Error catching.
-    std::thread::scope(|s| {
+    let usage = std::thread::scope(|s| {
+        // The sampler mostly waits, so it runs next to the rest.
+        let sampler = s.spawn(|| sample.and_then(fetch::cpu::sample));
         s.spawn(|| {
-            data.get_packages();
+            if !restored {
+                data.get_packages();
+            }
             data.get_shell();
             data.get_user();
-            data.get_distro();
+            if !restored {
+                data.get_distro();
+            }
             data.get_terminal();
-            data.get_uptime();
-            data.get_memory();
-            data.get_cpu();
+            data.get_uptime(uptime);
+            data.get_memory(memory);
+            data.get_disks(disk, memory);
+            if restored {
+                data.get_cpu_live();
+            } else {
+                data.get_cpu();
+                data.get_gpu();
+            }
         });
+        sampler.join().ok().flatten()
     });
-    data
+    if let Some((total, cores)) = usage {
+        data.set_cpu_usage(total, cores);
+    }
+    (data, restored)
 }
 
 #[cfg(test)]
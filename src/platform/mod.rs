@@ -2,6 +2,7 @@ mod arch;
 mod name;
 mod version;
 
+#[allow(dead_code)]
 pub struct Platform {
     pub name: String,
     pub version: String,
@@ -10,6 +11,7 @@ pub struct Platform {
 
 #[cfg(target_os = "linux")]
 impl Platform {
+    #[allow(dead_code)]
     pub fn gen(&mut self) -> Self {
         let name = name::Name {
             name: String::from(""),
@@ -19,13 +19,13 @@ pub fn rand(min_value: usize, max_value: usize) -> usize {
 }
 
 thread_local! {
-    static SEED: RefCell<u128> = RefCell::new(0);
+    static SEED: RefCell<u128> = const { RefCell::new(0) };
 
-    static X0: RefCell<u128> = RefCell::new(0);
-    static X1: RefCell<u128> = RefCell::new(0);
-    static X2: RefCell<u128> = RefCell::new(0);
-    static X3: RefCell<u128> = RefCell::new(0);
-    static X4: RefCell<u128> = RefCell::new(0);
+    static X0: RefCell<u128> = const { RefCell::new(0) };
+    static X1: RefCell<u128> = const { RefCell::new(0) };
+    static X2: RefCell<u128> = const { RefCell::new(0) };
+    static X3: RefCell<u128> = const { RefCell::new(0) };
+    static X4: RefCell<u128> = const { RefCell::new(0) };
 }
 
 /// Sets the seed for the random number generator.
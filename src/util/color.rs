@@ -0,0 +1,316 @@
+use crate::util::hyperstr::palette;
+
+/// How many colors we are allowed to use for the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Mono,
+}
+
+impl ColorDepth {
+    /// Create the escape sequence for a foreground color.
+    ///
+    /// # Arguments:
+    /// * `rgb`: The color.
+    ///
+    /// # Returns:
+    /// The escape sequence, empty for `Mono`.
+    ///
+    pub fn fg(&self, rgb: [u8; 3]) -> String {
+        match self {
+            ColorDepth::TrueColor => format!("\x1b[38;2;{};{};{}m", rgb[0], rgb[1], rgb[2]),
+            ColorDepth::Ansi256 => format!("\x1b[38;5;{}m", to_ansi256(rgb)),
+            ColorDepth::Ansi16 => palette()[to_ansi16(rgb) as usize].to_string(),
+            ColorDepth::Mono => String::new(),
+        }
+    }
+
+    /// Create the escape sequence for a background color.
+    ///
+    /// # Arguments:
+    /// * `rgb`: The color.
+    ///
+    /// # Returns:
+    /// The escape sequence, empty for `Mono`.
+    ///
+    pub fn bg(&self, rgb: [u8; 3]) -> String {
+        match self {
+            ColorDepth::TrueColor => format!("\x1b[48;2;{};{};{}m", rgb[0], rgb[1], rgb[2]),
+            ColorDepth::Ansi256 => format!("\x1b[48;5;{}m", to_ansi256(rgb)),
+            ColorDepth::Ansi16 => match to_ansi16(rgb) {
+                i if i < 8 => format!("\x1b[{}m", 40 + i),
+                i => format!("\x1b[{}m", 100 + i - 8),
+            },
+            ColorDepth::Mono => String::new(),
+        }
+    }
+
+    /// Find the color that actually gets displayed in this depth.
+    ///
+    /// # Arguments:
+    /// * `rgb`: The color.
+    ///
+    /// # Returns:
+    /// The closest color of the palette, black or white for `Mono`.
+    ///
+    pub fn nearest(&self, rgb: [u8; 3]) -> [u8; 3] {
+        match self {
+            ColorDepth::TrueColor => rgb,
+            ColorDepth::Ansi256 => ansi256_rgb(to_ansi256(rgb)),
+            ColorDepth::Ansi16 => ANSI16[to_ansi16(rgb) as usize],
+            ColorDepth::Mono if luminance(rgb) >= 0.5 => [255; 3],
+            ColorDepth::Mono => [0; 3],
+        }
+    }
+}
+
+/// The names of the basic 16 colors, in the order of the palette.
+/// Same as the color placeholders, `{light_red}` is `light_red`.
+pub const COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "gray",
+    "light_red",
+    "light_green",
+    "light_yellow",
+    "light_blue",
+    "light_magenta",
+    "light_cyan",
+    "light_gray",
+];
+
+/// A color from the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// An index in the 256 color palette, the first 16 follow the theme of the terminal.
+    Indexed(u8),
+    Rgb([u8; 3]),
+}
+
+impl Color {
+    /// Parse a color: a name like `light_red`, `#rrggbb`, `rgb(r, g, b)` or an index in the 256 color palette.
+    ///
+    /// # Arguments:
+    /// * `text`: The color.
+    ///
+    /// # Returns:
+    /// The color, `None` if it isn't one.
+    ///
+    pub fn parse(text: &str) -> Option<Color> {
+        let text = text.trim().to_lowercase();
+        let name = text.replace([' ', '-'], "_");
+        if let Some(i) = COLOR_NAMES.iter().position(|n| *n == name) {
+            return Some(Color::Indexed(i as u8));
+        }
+        if let Some(hex) = text.strip_prefix('#') {
+            let value = u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.len() == 6)?;
+            return Some(Color::Rgb([
+                (value >> 16) as u8,
+                (value >> 8) as u8,
+                value as u8,
+            ]));
+        }
+        if let Some(values) = text
+            .strip_prefix("rgb(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let values: Vec<u8> = values
+                .split(',')
+                .map(|v| v.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            return <[u8; 3]>::try_from(values).ok().map(Color::Rgb);
+        }
+        text.parse().ok().map(Color::Indexed)
+    }
+
+    /// Create the escape sequence for the color as foreground.
+    ///
+    /// # Arguments:
+    /// * `depth`: How many colors can be used.
+    ///
+    /// # Returns:
+    /// The escape sequence, empty for `Mono`.
+    ///
+    pub fn fg(&self, depth: ColorDepth) -> String {
+        match (self, depth) {
+            (_, ColorDepth::Mono) => String::new(),
+            (Color::Indexed(i), _) if *i < 16 => palette()[*i as usize].to_string(),
+            (Color::Indexed(i), ColorDepth::TrueColor | ColorDepth::Ansi256) => {
+                format!("\x1b[38;5;{}m", i)
+            }
+            (Color::Indexed(i), _) => depth.fg(ansi256_rgb(*i)),
+            (Color::Rgb(rgb), _) => depth.fg(*rgb),
+        }
+    }
+}
+
+/// The basic 16 colors, as xterm displays them by default.
+/// The actual colors depend on the theme of the terminal, but this is close enough.
+const ANSI16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// Find the closest of the basic 16 colors.
+///
+/// # Arguments:
+/// * `rgb`: The color.
+///
+/// # Returns:
+/// The index of the color, 0-7 are the normal and 8-15 the bright colors.
+///
+pub fn to_ansi16(rgb: [u8; 3]) -> u8 {
+    ANSI16
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| distance(rgb, **c))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// Get the color of an index of the 256 color palette.
+/// Only the color cube and the grayscale ramp, see `to_ansi256`.
+///
+/// # Arguments:
+/// * `index`: The index in the 256 color palette.
+///
+/// # Returns:
+/// The color.
+///
+pub fn ansi256_rgb(index: u8) -> [u8; 3] {
+    match index {
+        232..=255 => [8 + (index - 232) * 10; 3],
+        16..=231 => {
+            let i = index - 16;
+            [
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            ]
+        }
+        _ => ANSI16[index as usize],
+    }
+}
+
+/// The levels of the 6x6x6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Find the closest color of the 256 color palette.
+/// Only the color cube and the grayscale ramp are used, the first 16 colors depend on the terminal theme.
+///
+/// # Arguments:
+/// * `rgb`: The color.
+///
+/// # Returns:
+/// The index in the 256 color palette.
+///
+pub fn to_ansi256(rgb: [u8; 3]) -> u8 {
+    let cube = rgb.map(|v| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, l)| (**l as i32 - v as i32).abs())
+            .map_or(0, |(i, _)| i as u8)
+    });
+    let cube_rgb = cube.map(|i| CUBE_LEVELS[i as usize]);
+
+    // The grayscale ramp goes from 8 to 238 in steps of 10.
+    let avg = (rgb[0] as u32 + rgb[1] as u32 + rgb[2] as u32) / 3;
+    let gray = ((avg.saturating_sub(3)) / 10).min(23) as u8;
+    let gray_value = 8 + gray * 10;
+
+    if distance(rgb, [gray_value; 3]) < distance(rgb, cube_rgb) {
+        232 + gray
+    } else {
+        16 + 36 * cube[0] + 6 * cube[1] + cube[2]
+    }
+}
+
+/// The squared distance between two colors.
+pub fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (*x as i32 - *y as i32).pow(2) as u32)
+        .sum()
+}
+
+/// The perceived brightness of a color, from 0 to 1.
+pub fn luminance(rgb: [u8; 3]) -> f32 {
+    (0.2126 * rgb[0] as f32 + 0.7152 * rgb[1] as f32 + 0.0722 * rgb[2] as f32) / 255.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi256() {
+        assert_eq!(to_ansi256([0, 0, 0]), 16);
+        assert_eq!(to_ansi256([255, 255, 255]), 231);
+        assert_eq!(to_ansi256([255, 0, 0]), 196);
+        assert_eq!(to_ansi256([128, 128, 128]), 244);
+    }
+
+    #[test]
+    fn test_fg() {
+        assert_eq!(ColorDepth::TrueColor.fg([1, 2, 3]), "\x1b[38;2;1;2;3m");
+        assert_eq!(ColorDepth::Ansi256.fg([255, 0, 0]), "\x1b[38;5;196m");
+        assert_eq!(ColorDepth::Ansi16.fg([250, 10, 0]), "\x1b[91m");
+        assert_eq!(ColorDepth::Ansi16.bg([0, 0, 0]), "\x1b[40m");
+        assert_eq!(ColorDepth::Mono.fg([255, 0, 0]), "");
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(Color::parse("Light Red"), Some(Color::Indexed(9)));
+        assert_eq!(Color::parse("#FF8800"), Some(Color::Rgb([255, 136, 0])));
+        assert_eq!(Color::parse("rgb(1, 2,3)"), Some(Color::Rgb([1, 2, 3])));
+        assert_eq!(Color::parse("208"), Some(Color::Indexed(208)));
+        for text in ["256", "#ff88", "rgb(1, 2)", "rgb(1, 2, 300)", "pink"] {
+            assert_eq!(Color::parse(text), None, "{}", text);
+        }
+
+        assert_eq!(Color::Indexed(1).fg(ColorDepth::TrueColor), "\x1b[31m");
+        assert_eq!(Color::Indexed(9).fg(ColorDepth::Ansi16), "\x1b[91m");
+        assert_eq!(
+            Color::Indexed(196).fg(ColorDepth::Ansi256),
+            "\x1b[38;5;196m"
+        );
+        assert_eq!(Color::Indexed(196).fg(ColorDepth::Ansi16), "\x1b[91m");
+        assert_eq!(Color::Rgb([1, 2, 3]).fg(ColorDepth::Mono), "");
+    }
+
+    #[test]
+    fn test_nearest() {
+        for i in 16..=255 {
+            assert_eq!(to_ansi256(ansi256_rgb(i)), i);
+        }
+        assert_eq!(ColorDepth::TrueColor.nearest([1, 2, 3]), [1, 2, 3]);
+        assert_eq!(ColorDepth::Ansi16.nearest([250, 10, 0]), [255, 0, 0]);
+        assert_eq!(ColorDepth::Mono.nearest([200, 200, 200]), [255; 3]);
+        assert_eq!(ColorDepth::Mono.nearest([20, 20, 20]), [0; 3]);
+    }
+}
@@ -1,9 +1,10 @@
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
-use std::{env, io};
+use std::time::UNIX_EPOCH;
+use std::{env, fs, io};
 
-/// # This file is a bit messy, but i can't be bothered to clean it up.
+// This file is a bit messy, but i can't be bothered to clean it up.
 
 /// Allows you to read environmental variables.
 ///
@@ -48,10 +49,12 @@ pub fn get_data<P: AsRef<Path>>(file_path: P, size: usize) -> io::Result<String>
     file_data(&mut file, size)
 }
 
-/// Gets a fake hash of a file.
+/// Hashes a file by its content, its modification time and the parameters it gets rendered with.
+/// Any change to one of them results in a different hash.
 ///
 /// # Arguments:
-/// * `file_path`: The path to the file you want to read.
+/// * `path`: The path to the file you want to hash.
+/// * `params`: Everything else that changes the output, e.g. the render settings.
 ///
 /// # Returns:
 /// The hash of the file as a String.
@@ -59,14 +62,58 @@ pub fn get_data<P: AsRef<Path>>(file_path: P, size: usize) -> io::Result<String>
 /// # Example:
 /// ```
 /// use lightfetch::files::get_hash;
-/// let hash = get_hash("/home/bwte/.config/lightfetch/cache/1.png");
+/// let hash = get_hash(Path::new("/home/bwte/.config/lightfetch/images/default.png"), "size=15");
 /// ```
 ///
-pub fn get_fake_hash(path: &Path, size: u32, filter: String) -> String {
-    let mut out = String::with_capacity(32);
-    let metadata = File::open(path).unwrap().metadata().unwrap();
-    out.push_str(&metadata.len().to_string());
-    out.push_str(size.to_string().as_str());
-    out.push_str(filter.as_str());
-    out
+pub fn get_hash(path: &Path, params: &str) -> io::Result<String> {
+    let content = fs::read(path)?;
+    let modified = fs::metadata(path)?
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let mut hash = fnv1a(FNV_OFFSET, &content);
+    hash = fnv1a(hash, &modified.as_secs().to_le_bytes());
+    hash = fnv1a(hash, &modified.subsec_nanos().to_le_bytes());
+    hash = fnv1a(hash, params.as_bytes());
+    Ok(format!("{:016x}", hash))
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// The 64 bit FNV-1a hash, it's fast and good enough to tell cache entries apart.
+/// http://www.isthe.com/chongo/tech/comp/fnv/
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(FNV_OFFSET, b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_get_hash() {
+        let path = env::temp_dir().join("lightfetch_hash_test.txt");
+        fs::write(&path, "abc").unwrap();
+        let first = get_hash(&path, "size=15").unwrap();
+        assert_eq!(first, get_hash(&path, "size=15").unwrap());
+        assert_ne!(first, get_hash(&path, "size=16").unwrap());
+
+        // Same length, different content.
+        fs::write(&path, "abd").unwrap();
+        assert_ne!(first, get_hash(&path, "size=15").unwrap());
+        fs::remove_file(&path).unwrap();
+        assert!(get_hash(&path, "size=15").is_err());
+    }
 }
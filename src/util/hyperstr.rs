@@ -1,4 +1,5 @@
 use crate::create_spaces;
+use crate::util::color::{Color, ColorDepth};
 use std::collections::HashMap;
 
 /// # A couple of colors in case we need them later on.
@@ -36,45 +37,84 @@ pub static N: &str = "\r\n";
 #[cfg(not(windows))]
 pub static N: &str = "\n";
 
+/// The basic 16 colors, in the order of the palette.
+pub fn palette() -> [&'static str; 16] {
+    [
+        BLACK,
+        RED,
+        GREEN,
+        YELLOW,
+        BLUE,
+        MAGENTA,
+        CYAN,
+        WHITE,
+        GRAY,
+        LIGHT_RED,
+        LIGHT_GREEN,
+        LIGHT_YELLOW,
+        LIGHT_BLUE,
+        LIGHT_MAGENTA,
+        LIGHT_CYAN,
+        LIGHT_GRAY,
+    ]
+}
+
+/// The placeholders of the basic 16 colors, in the order of the palette.
+const COLOR_PLACEHOLDERS: [&str; 16] = [
+    "BLACK",
+    "RED",
+    "GREEN",
+    "YELLOW",
+    "BLUE",
+    "MAGENTA",
+    "CYAN",
+    "WHITE",
+    "GRAY",
+    "LIGHT_RED",
+    "LIGHT_GREEN",
+    "LIGHT_YELLOW",
+    "LIGHT_BLUE",
+    "LIGHT_MAGENTA",
+    "LIGHT_CYAN",
+    "LIGHT_GRAY",
+];
+
 /// # Outputs a HashMap with ASCII color values.
+/// ## The colors are from the basic 16 color palette, so every terminal with colors supports them.
+///
+/// # Arguments:
+/// * `depth`: How many colors the terminal can show. With `Mono` the colors are replaced with nothing, formatting is kept.
 ///
 /// # Returns:
 /// The HashMap with the colors.
 ///
-pub fn colormap() -> HashMap<&'static str, &'static str> {
-    let mut table = HashMap::new();
-    table.insert("BLACK", BLACK);
-    table.insert("RED", RED);
-    table.insert("GREEN", GREEN);
-    table.insert("YELLOW", YELLOW);
-    table.insert("BLUE", BLUE);
-    table.insert("MAGENTA", MAGENTA);
-    table.insert("CYAN", CYAN);
-    table.insert("WHITE", WHITE);
-    table.insert("GRAY", GRAY);
-    table.insert("LIGHT_RED", LIGHT_RED);
-    table.insert("LIGHT_GREEN", LIGHT_GREEN);
-    table.insert("LIGHT_YELLOW", LIGHT_YELLOW);
-    table.insert("LIGHT_BLUE", LIGHT_BLUE);
-    table.insert("LIGHT_MAGENTA", LIGHT_MAGENTA);
-    table.insert("LIGHT_CYAN", LIGHT_CYAN);
-    table.insert("LIGHT_GRAY", LIGHT_GRAY);
-    table.insert("RESET", RESET);
-    table.insert("BOLD", BOLD);
-    table.insert("UNDERLINE", UNDERLINE);
-    table.insert("REVERSE", REVERSE);
-    table.insert("BLINK", BLINK);
-    table.insert("INVISIBLE", HIDDEN);
-    table.insert("CROSSED", CROSSED);
-    table.insert("ITALIC", ITALIC);
-    table.insert("R", RESET);
-    table.insert("B", BOLD);
-    table.insert("U", UNDERLINE);
-    table.insert("R", REVERSE);
-    table.insert("BL", BLINK);
-    table.insert("IN", HIDDEN);
-    table.insert("C", CROSSED);
-    table.insert("I", ITALIC);
+pub fn colormap(depth: ColorDepth) -> HashMap<&'static str, String> {
+    let mut table: HashMap<&'static str, String> = COLOR_PLACEHOLDERS
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (*name, Color::Indexed(i as u8).fg(depth)))
+        .collect();
+    let formatting = [
+        ("RESET", RESET),
+        ("BOLD", BOLD),
+        ("UNDERLINE", UNDERLINE),
+        ("REVERSE", REVERSE),
+        ("BLINK", BLINK),
+        ("INVISIBLE", HIDDEN),
+        ("CROSSED", CROSSED),
+        ("ITALIC", ITALIC),
+        ("R", RESET),
+        ("B", BOLD),
+        ("U", UNDERLINE),
+        ("R", REVERSE),
+        ("BL", BLINK),
+        ("IN", HIDDEN),
+        ("C", CROSSED),
+        ("I", ITALIC),
+    ];
+    for (name, value) in formatting {
+        table.insert(name, value.to_string());
+    }
     table
 }
 
@@ -125,35 +165,40 @@ impl Ansi for str {
     /// ```
     ///
     /// # Note:
-    /// ## This function is really bad and should be replaced with a better one.
-    /// ## Though this should work fine for now.
+    /// ## Any escape sequence is stripped, not just colors.
+    /// ## This includes the graphics protocols (APC, OSC, DCS) used by the image renderers.
     fn strip_ansi_colors(&self) -> String {
-        self // Formatting.
-            .replace("\x1b[0m", "")
-            .replace("\x1b[1m", "")
-            .replace("\x1b[4m", "")
-            .replace("\x1b[7m", "")
-            .replace("\x1b[5m", "")
-            .replace("\x1b[8m", "")
-            .replace("\x1b[9m", "")
-            .replace("\x1b[3m", "")
-            // Colors.
-            .replace("\x1b[30m", "")
-            .replace("\x1b[31m", "")
-            .replace("\x1b[32m", "")
-            .replace("\x1b[33m", "")
-            .replace("\x1b[34m", "")
-            .replace("\x1b[35m", "")
-            .replace("\x1b[36m", "")
-            .replace("\x1b[37m", "")
-            // Background colors.
-            .replace("\x1b[90m", "")
-            .replace("\x1b[91m", "")
-            .replace("\x1b[92m", "")
-            .replace("\x1b[93m", "")
-            .replace("\x1b[94m", "")
-            .replace("\x1b[95m", "")
-            .replace("\x1b[96m", "")
-            .replace("\x1b[97m", "")
+        let mut out = String::with_capacity(self.len());
+        let mut chars = self.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                // CSI: Ends with a byte in the range '@'..='~'.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // APC, OSC, DCS: Ends with the string terminator or BEL.
+                Some('_') | Some(']') | Some('P') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
     }
 }
@@ -1,3 +1,5 @@
+pub mod base64;
+pub mod color;
 pub mod constants;
 pub mod data;
 pub mod hyperstr;
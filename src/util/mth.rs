@@ -1,74 +1,102 @@
-/// Format a number of bytes as a String in GB, or MB, or KB.
-///
-/// # Arguments:
-/// * `value`: The value to format.
-///
-/// # Returns:
-/// A String containing the formatted value.
-///
-pub fn format_gb_str(value: f64) -> String {
-    let precision = 2;
-    if value > 1000.0 {
-        let mut out = round(value / 1000.0, 1 + precision);
-        out.push_str(" GB");
-        return out;
+/// Whether sizes are counted in steps of 1024 (KiB, MiB, GiB, TiB) or 1000 (KB, MB, GB, TB).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Binary,
+    Decimal,
+}
+
+impl Units {
+    /// The suffixes of kilo-, mega-, giga- and terabytes in these units, e.g. ` KiB` for binary.
+    pub fn suffixes(&self) -> [&'static str; 4] {
+        match self {
+            Units::Binary => [" KiB", " MiB", " GiB", " TiB"],
+            Units::Decimal => [" KB", " MB", " GB", " TB"],
+        }
     }
-    if value > 1.0 {
-        let mut out = round(value, 1 + precision);
-        out.push_str(" MB");
-        return out;
+}
+
+/// How sizes are formatted, set in the `[MEMORY]` section of the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeFormat {
+    /// The number of decimal places.
+    pub rounding: usize,
+    pub units: Units,
+    /// The suffixes of kilo-, mega-, giga- and terabytes.
+    pub suffixes: [String; 4],
+}
+
+impl SizeFormat {
+    /// Create the format from the values of the config.
+    /// The default suffixes are the decimal ones, suffixes left at them follow the units instead.
+    ///
+    /// # Arguments:
+    /// * `rounding`: The number of decimal places.
+    /// * `units`: Whether to count in steps of 1024 or 1000.
+    /// * `suffixes`: The suffixes set in the config.
+    ///
+    pub fn new(rounding: usize, units: Units, mut suffixes: [String; 4]) -> Self {
+        let defaults = Units::Decimal.suffixes();
+        for (i, suffix) in suffixes.iter_mut().enumerate() {
+            if *suffix == defaults[i] {
+                *suffix = units.suffixes()[i].to_string();
+            }
+        }
+        SizeFormat {
+            rounding,
+            units,
+            suffixes,
+        }
     }
-    let mut out = round(value, 1 + precision);
-    out.push_str(" KB");
-    out
 }
-/// Rust doesn't have a built-in way to round a number to a certain number of decimal places.
-/// So we use this function to do it for us.
-///
-/// It's not perfect, but it's good enough.
-///
-///
-/// Credits: https://stackoverflow.com/questions/60497397
+
+impl Default for SizeFormat {
+    fn default() -> Self {
+        SizeFormat {
+            rounding: 2,
+            units: Units::Decimal,
+            suffixes: Units::Decimal.suffixes().map(String::from),
+        }
+    }
+}
+
+/// Format a number of kibibytes as a String in TB, or GB, or MB, or KB.
 ///
 /// # Arguments:
-/// * `value`: The number to round.
-/// * `precision`: The number of decimal places to round to.
+/// * `value`: The value to format, in KiB like in `/proc/meminfo`.
+/// * `format`: The rounding, units and suffixes to use.
 ///
 /// # Returns:
-/// The rounded number as a String.
+/// A String containing the formatted value.
 ///
-fn round(value: f64, precision: usize) -> String {
-    let a = value.abs();
-    let precision = if a >= 1. {
-        let n = (1. + a.log10().floor()) as usize;
-        if n <= precision {
-            precision - n
-        } else {
-            0
-        }
-    } else if a > 0. {
-        let n = -(1. + a.log10().floor()) as usize;
-        precision + n
-    } else {
-        0
+pub fn format_gb_str(value: f64, format: &SizeFormat) -> String {
+    let (base, value) = match format.units {
+        Units::Binary => (1024.0, value),
+        Units::Decimal => (1000.0, value * 1024.0 / 1000.0),
     };
-    format!("{0:.1$}", value, precision)
+    // The biggest unit the value has at least one of.
+    let mut step = 0;
+    let mut value = value;
+    while value >= base && step < format.suffixes.len() - 1 {
+        value /= base;
+        step += 1;
+    }
+    format!("{:.2$}{}", value, format.suffixes[step], format.rounding)
 }
 
-/// Limit the number so it doesn't go over the set maximum value.
+/// Get how much of a total is used, in percent.
 ///
 /// # Arguments:
-/// * `f`: The number to floor
-/// * `max`: The maximum value that the function can return.
+/// * `used`: The used part.
+/// * `total`: The total.
 ///
 /// # Returns:
-/// Returns the limited value if `f` is higher than `max`, otherwise returns the unmodified value.
+/// The rounded percentage, 0 if the total is 0.
 ///
-pub fn floor(f: f32, max: f32) -> f32 {
-    if f > max {
-        max
+pub fn percent(used: f64, total: f64) -> String {
+    if total > 0.0 {
+        format!("{:.0}", used / total * 100.0)
     } else {
-        f
+        "0".to_string()
     }
 }
 
@@ -100,3 +128,32 @@ pub fn to_vector(map: String) -> Vec<String> {
     }
     vec
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_gb_str() {
+        let decimal = SizeFormat::default();
+        assert_eq!(format_gb_str(500.0, &decimal), "512.00 KB");
+        assert_eq!(format_gb_str(16_000_000.0, &decimal), "16.38 GB");
+        // The decimal suffixes are the defaults, binary units get their own.
+        let binary = SizeFormat::new(1, Units::Binary, decimal.suffixes.clone());
+        assert_eq!(format_gb_str(1024.0, &binary), "1.0 MiB");
+        assert_eq!(format_gb_str(16_000_000.0, &binary), "15.3 GiB");
+        assert_eq!(format_gb_str(0.0, &binary), "0.0 KiB");
+
+        assert_eq!(format_gb_str(3.0 * 1024f64.powi(3), &binary), "3.0 TiB");
+        assert_eq!(format_gb_str(4e12, &decimal), "4096.00 TB");
+
+        let custom = SizeFormat::new(0, Units::Binary, ["k", " MB", "G", " TB"].map(String::from));
+        assert_eq!(custom.suffixes, ["k", " MiB", "G", " TiB"]);
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(percent(1.0, 3.0), "33");
+        assert_eq!(percent(5.0, 0.0), "0");
+    }
+}
@@ -0,0 +1,372 @@
+use std::time::Duration;
+
+use crate::util::color::ColorDepth;
+use crate::util::terminal::unicode::supports_unicode;
+
+/// The best way the terminal can display an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Graphics {
+    Kitty,
+    Iterm,
+    Sixel,
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Ascii,
+}
+
+impl Graphics {
+    /// Detect the graphics support of the current terminal.
+    ///
+    /// # Arguments:
+    /// * `timeout`: How long to wait for the terminal to answer the query, `None` to skip querying.
+    ///
+    /// # Returns:
+    /// The best supported `Graphics`.
+    ///
+    pub fn detect(timeout: Option<Duration>) -> Self {
+        let reply = timeout.and_then(query).map(|r| Reply::parse(&r));
+        detect_with(&|key| std::env::var(key).ok(), supports_unicode(), reply)
+    }
+
+    /// The `[ART] mode` rendering this kind of graphics.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            Graphics::Kitty => "kitty",
+            Graphics::Iterm => "iterm",
+            Graphics::Sixel => "sixel",
+            Graphics::TrueColor | Graphics::Ansi256 | Graphics::Ansi16 => "image",
+            Graphics::Ascii => "characters",
+        }
+    }
+
+    /// The color depth that goes along with it.
+    pub fn depth(&self) -> ColorDepth {
+        match self {
+            Graphics::Ansi256 => ColorDepth::Ansi256,
+            Graphics::Ansi16 => ColorDepth::Ansi16,
+            Graphics::Ascii => ColorDepth::Mono,
+            _ => ColorDepth::TrueColor,
+        }
+    }
+}
+
+/// What the terminal told us about itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reply {
+    pub sixel: bool,
+    pub truecolor: bool,
+}
+
+impl Reply {
+    /// Parse the answer to the XTGETTCAP and DA1 queries.
+    ///
+    /// # Arguments:
+    /// * `reply`: Everything the terminal sent back.
+    ///
+    /// # Returns:
+    /// The parsed `Reply`.
+    ///
+    pub fn parse(reply: &str) -> Self {
+        // DA1: ESC [ ? 62 ; 4 ; 22 c, attribute 4 means sixel.
+        let sixel = reply
+            .find("\x1b[?")
+            .and_then(|start| {
+                let rest = &reply[start + 3..];
+                rest.find('c').map(|end| &rest[..end])
+            })
+            .is_some_and(|attributes| attributes.split(';').any(|a| a == "4"));
+        // XTGETTCAP: ESC P 1 + r <hex name> = <hex value> ESC \, "524742" is "RGB".
+        let truecolor = reply.contains("\x1bP1+r524742");
+        Self { sixel, truecolor }
+    }
+}
+
+/// Check if colors should be used at all.
+/// See https://no-color.org for `NO_COLOR`.
+///
+/// # Returns:
+/// False if the user or the terminal doesn't want colors.
+///
+fn colors_with(env: &dyn Fn(&str) -> Option<String>) -> bool {
+    let term = env("TERM").unwrap_or_default();
+    env("NO_COLOR").is_none_or(|v| v.is_empty()) && term != "dumb"
+}
+
+/// How many colors the text can use, only the environment is checked.
+///
+/// # Returns:
+/// The `ColorDepth`, `Mono` if the user or the terminal doesn't want colors.
+///
+pub fn color_depth() -> ColorDepth {
+    depth_with(&|key| std::env::var(key).ok())
+}
+
+fn depth_with(env: &dyn Fn(&str) -> Option<String>) -> ColorDepth {
+    if !colors_with(env) {
+        return ColorDepth::Mono;
+    }
+    let colorterm = env("COLORTERM").unwrap_or_default().to_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return ColorDepth::TrueColor;
+    }
+    if env("TERM")
+        .unwrap_or_default()
+        .to_lowercase()
+        .contains("256color")
+    {
+        return ColorDepth::Ansi256;
+    }
+    ColorDepth::Ansi16
+}
+
+/// Pick the graphics from the environment and the reply of the terminal.
+///
+/// # Arguments:
+/// * `env`: Gets an environment variable.
+/// * `unicode`: Does the terminal support unicode? The half blocks need it.
+/// * `reply`: The answer of the terminal, if it was queried.
+///
+/// # Returns:
+/// The best supported `Graphics`.
+///
+fn detect_with(
+    env: &dyn Fn(&str) -> Option<String>,
+    unicode: bool,
+    reply: Option<Reply>,
+) -> Graphics {
+    let reply = reply.unwrap_or_default();
+    let term = env("TERM").unwrap_or_default().to_lowercase();
+    let program = env("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+
+    if !colors_with(env) {
+        return Graphics::Ascii;
+    }
+
+    // Multiplexers don't pass graphics through.
+    let multiplexer =
+        env("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux");
+    if !multiplexer {
+        if env("KITTY_WINDOW_ID").is_some() || term.contains("kitty") || program == "ghostty" {
+            return Graphics::Kitty;
+        }
+        if program == "iterm.app"
+            || program == "wezterm"
+            || env("LC_TERMINAL").is_some_and(|t| t == "iTerm2")
+        {
+            return Graphics::Iterm;
+        }
+        if reply.sixel
+            || ["foot", "mlterm", "contour"]
+                .iter()
+                .any(|t| term.starts_with(t))
+        {
+            return Graphics::Sixel;
+        }
+    }
+
+    if !unicode {
+        return Graphics::Ascii;
+    }
+    if reply.truecolor {
+        return Graphics::TrueColor;
+    }
+    match depth_with(env) {
+        ColorDepth::TrueColor => Graphics::TrueColor,
+        ColorDepth::Ansi256 => Graphics::Ansi256,
+        _ => Graphics::Ansi16,
+    }
+}
+
+/// Ask the terminal for its capabilities.
+/// XTGETTCAP asks for truecolor support, DA1 lists sixel support.
+/// Every terminal answers DA1, so we know when to stop reading.
+///
+/// # Arguments:
+/// * `timeout`: How long to wait for the answer.
+///
+/// # Returns:
+/// The raw answer, or `None` if this isn't an interactive terminal.
+///
+#[cfg(unix)]
+fn query(timeout: Duration) -> Option<String> {
+    use std::io::Write;
+    use std::time::Instant;
+
+    let fd = libc::STDIN_FILENO;
+    // SAFETY: All calls only work on the terminal attributes of stdin and our own buffers.
+    unsafe {
+        if libc::isatty(fd) == 0 || libc::isatty(libc::STDOUT_FILENO) == 0 {
+            return None;
+        }
+        let mut old: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut old) != 0 {
+            return None;
+        }
+        // No echo and no line buffering, otherwise the answer shows up on the screen.
+        let mut raw = old;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+            return None;
+        }
+
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x1bP+q524742\x1b\\\x1b[c");
+        let _ = stdout.flush();
+
+        let deadline = Instant::now() + timeout;
+        let mut reply = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            let mut pfd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            if libc::poll(&mut pfd, 1, remaining.as_millis() as libc::c_int) <= 0 {
+                break;
+            }
+            let mut buf = [0u8; 256];
+            let n = libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len());
+            if n <= 0 {
+                break;
+            }
+            reply.extend_from_slice(&buf[..n as usize]);
+            if let Some(start) = reply.windows(3).position(|w| w == b"\x1b[?") {
+                if reply[start..].contains(&b'c') {
+                    break;
+                }
+            }
+        }
+
+        libc::tcsetattr(fd, libc::TCSANOW, &old);
+        Some(String::from_utf8_lossy(&reply).into_owned())
+    }
+}
+
+#[cfg(not(unix))]
+fn query(_timeout: Duration) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)], unicode: bool, reply: Option<Reply>) -> Graphics {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        detect_with(&|key| vars.get(key).cloned(), unicode, reply)
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty")], true, None),
+            Graphics::Kitty
+        );
+        assert_eq!(
+            detect(&[("KITTY_WINDOW_ID", "1")], true, None),
+            Graphics::Kitty
+        );
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "WezTerm")], true, None),
+            Graphics::Iterm
+        );
+        assert_eq!(detect(&[("TERM", "foot")], true, None), Graphics::Sixel);
+        assert_eq!(
+            detect(
+                &[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")],
+                true,
+                None
+            ),
+            Graphics::TrueColor
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color")], true, None),
+            Graphics::Ansi256
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color")], false, None),
+            Graphics::Ascii
+        );
+        assert_eq!(detect(&[("TERM", "linux")], true, None), Graphics::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")], true, None), Graphics::Ascii);
+    }
+
+    #[test]
+    fn test_detect_reply() {
+        let reply = Reply {
+            sixel: true,
+            truecolor: true,
+        };
+        assert_eq!(
+            detect(&[("TERM", "xterm")], true, Some(reply)),
+            Graphics::Sixel
+        );
+        // No graphics inside of tmux.
+        assert_eq!(
+            detect(&[("TERM", "tmux-256color")], true, Some(reply)),
+            Graphics::TrueColor
+        );
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(
+            Reply::parse("\x1bP1+r524742=382F382F38\x1b\\\x1b[?62;4;22c"),
+            Reply {
+                sixel: true,
+                truecolor: true
+            }
+        );
+        assert_eq!(Reply::parse("\x1bP0+r\x1b\\\x1b[?1;2c"), Reply::default());
+        assert_eq!(Reply::parse(""), Reply::default());
+    }
+
+    #[test]
+    fn test_supports_color() {
+        assert!(colors_with(&|_| None));
+        assert!(!colors_with(
+            &|key| (key == "NO_COLOR").then(|| "1".to_string())
+        ));
+        assert!(!colors_with(
+            &|key| (key == "TERM").then(|| "dumb".to_string())
+        ));
+    }
+
+    #[test]
+    fn test_color_depth() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(depth_with(&env(&[])), ColorDepth::Ansi16);
+        assert_eq!(
+            depth_with(&env(&[("TERM", "xterm-256color")])),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            depth_with(&env(&[
+                ("TERM", "xterm-256color"),
+                ("COLORTERM", "truecolor")
+            ])),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            depth_with(&env(&[("COLORTERM", "24bit"), ("NO_COLOR", "1")])),
+            ColorDepth::Mono
+        );
+    }
+}
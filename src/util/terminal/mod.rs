@@ -1,3 +1,5 @@
+pub mod detect;
+pub mod size;
 pub mod unicode;
 
 #[cfg(test)]
@@ -0,0 +1,35 @@
+/// Most terminals are somewhere around this, used if the terminal doesn't tell us.
+pub const DEFAULT_CELL_SIZE: (u32, u32) = (10, 20);
+
+/// Get the size of a single terminal cell in pixels.
+///
+/// The terminal reports its size in pixels and cells, so we just divide them.
+///
+/// # Returns:
+/// The width and height of a cell, or `None` if the terminal doesn't report pixels.
+///
+#[cfg(unix)]
+pub fn cell_size() -> Option<(u32, u32)> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes into the winsize struct we pass in.
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    if result != 0 || size.ws_row == 0 || size.ws_col == 0 {
+        return None;
+    }
+    let width = size.ws_xpixel as u32 / size.ws_col as u32;
+    let height = size.ws_ypixel as u32 / size.ws_row as u32;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
+
+#[cfg(not(unix))]
+pub fn cell_size() -> Option<(u32, u32)> {
+    None
+}
@@ -32,6 +32,59 @@ impl<'t> Creator<'t> {
         Some(result)
     }
 
+    /// Find the placeholders that don't have a value.
+    ///
+    /// # Arguments:
+    /// * `var`: The values of all placeholders, like for `process_variables`.
+    ///
+    /// # Returns:
+    /// The unknown placeholders, as slices of the text.
+    ///
+    pub fn unknown_variables(&self, var: &HashMap<&str, &str>) -> Vec<&'t str> {
+        self.tokens
+            .iter()
+            .filter_map(|segment| match segment {
+                Type::Variable(s) if !var.contains_key(s.to_uppercase().as_str()) => Some(*s),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Find the tags that aren't closed or opened, like a `[U]` without `[/U]`.
+    /// Tags can't be nested, a tag opened inside of another one counts as unclosed as well.
+    ///
+    /// # Arguments:
+    /// * `text`: The text to check.
+    /// * `start`: The opening tag.
+    /// * `end`: The closing tag.
+    ///
+    /// # Returns:
+    /// The byte positions of the broken tags.
+    ///
+    pub fn unbalanced(text: &str, start: &str, end: &str) -> Vec<usize> {
+        let mut broken = Vec::new();
+        let mut open: Option<usize> = None;
+        let mut i = 0;
+        while let Some(c) = text[i..].chars().next() {
+            let rest = &text[i..];
+            if rest.starts_with(end) {
+                if open.take().is_none() {
+                    broken.push(i);
+                }
+                i += end.len();
+            } else if rest.starts_with(start) {
+                if let Some(previous) = open.replace(i) {
+                    broken.push(previous);
+                }
+                i += start.len();
+            } else {
+                i += c.len_utf8();
+            }
+        }
+        broken.extend(open);
+        broken
+    }
+
     #[allow(dead_code)]
     pub fn process_case(&self, i: u8) -> Option<String> {
         let mut result = String::new();
@@ -52,3 +105,25 @@ impl<'t> Creator<'t> {
         Some(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_variables() {
+        let var = HashMap::from([("USERNAME", "bwte")]);
+        let creator = Creator::init("{username} {nope} {USERNAME}", "{", "}");
+        assert_eq!(creator.unknown_variables(&var), vec!["nope"]);
+        assert_eq!(creator.process_variables(&var).unwrap(), "bwte  bwte");
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        assert!(Creator::unbalanced("[U]a[/U] [U]b[/U]", "[U]", "[/U]").is_empty());
+        assert_eq!(Creator::unbalanced("a[U]b", "[U]", "[/U]"), vec![1]);
+        assert_eq!(Creator::unbalanced("a[/U]", "[U]", "[/U]"), vec![1]);
+        assert_eq!(Creator::unbalanced("[U]a[U]b[/U]", "[U]", "[/U]"), vec![0]);
+        assert!(Creator::unbalanced("ö[U]ä[/U]", "[U]", "[/U]").is_empty());
+    }
+}